@@ -0,0 +1,95 @@
+//! Compares the scheduler the download workers share against the `Mutex<Vec<String>>` queue it replaced,
+//! which removed the first url under a lock for every attempt.
+
+extern crate criterion;
+extern crate image_search;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use image_search::scheduler::Scheduler;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const WORKERS: usize = 8;
+
+fn urls(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| format!("https://example.com/images/{}.jpg", i))
+        .collect()
+}
+
+/// The previous queue: every pop shifts the rest of the vector down while holding the lock.
+fn drain_mutex(urls: Vec<String>, workers: usize) -> usize {
+    let urls = Arc::new(Mutex::new(urls));
+    let handles: Vec<_> = (0..workers)
+        .map(|_| {
+            let urls = urls.clone();
+            thread::spawn(move || {
+                let mut taken = 0;
+                loop {
+                    let mut urls = urls.lock().unwrap();
+                    if urls.is_empty() {
+                        break taken;
+                    }
+                    criterion::black_box(urls.remove(0));
+                    taken += 1;
+                }
+            })
+        })
+        .collect();
+
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .sum()
+}
+
+fn drain_scheduler(urls: Vec<String>, workers: usize) -> usize {
+    let urls: Arc<Scheduler<String>> = Arc::new(urls.into_iter().collect());
+    let handles: Vec<_> = (0..workers)
+        .map(|_| {
+            let urls = urls.clone();
+            thread::spawn(move || {
+                let mut taken = 0;
+                while let Some(url) = urls.pop() {
+                    criterion::black_box(url);
+                    taken += 1;
+                }
+                taken
+            })
+        })
+        .collect();
+
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .sum()
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("drain");
+
+    for n in [100, 1_000, 10_000] {
+        for workers in [1, WORKERS] {
+            let id = format!("{}x{}", n, workers);
+            group.bench_with_input(BenchmarkId::new("mutex_vec", &id), &n, |b, &n| {
+                b.iter_batched(
+                    || urls(n),
+                    |urls| drain_mutex(urls, workers),
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+            group.bench_with_input(BenchmarkId::new("scheduler", &id), &n, |b, &n| {
+                b.iter_batched(
+                    || urls(n),
+                    |urls| drain_scheduler(urls, workers),
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -11,7 +11,7 @@ fn main() -> Result<(), image_search::Error> {
 
     let _image_urls = urls(args.clone())?;
     let _images = search(args.clone())?;
-    let _paths = download(args)?;
+    let _report = download(args)?;
 
     Ok(())
 }
@@ -0,0 +1,230 @@
+use super::{Parsed, SearchBackend, Strategy};
+use crate::{
+    Arguments, Color, ColorType, Image, ImageType, License, ParseStage, Ratio, SafeSearch, Size,
+    Time,
+};
+use surf::Url;
+
+/// Searches [Bing Images](https://www.bing.com/images).
+///
+/// Bing has no filter for the image format or for a custom date range, so [`Format`](crate::Format) and [`Time::Range`] are ignored,
+/// as is the country [`domain`](crate::Arguments::domain).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bing;
+
+impl SearchBackend for Bing {
+    fn name(&self) -> &'static str {
+        "bing"
+    }
+
+    fn build_url(&self, args: &Arguments, offset: usize) -> String {
+        let mut url = Url::parse("https://www.bing.com/images/search").expect("url is valid");
+
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("q", &args.query);
+
+        if let Some(language) = &args.language {
+            pairs.append_pair("setlang", language);
+        }
+        if let Some(region) = args.country.as_ref().or(args.region.as_ref()) {
+            pairs.append_pair("cc", region);
+        }
+
+        let safe = safe_search(args.safe_search);
+        if !safe.is_empty() {
+            pairs.append_pair("adlt", safe);
+        }
+
+        let filters: Vec<String> = [
+            color(args.color).to_owned(),
+            color_type(args.color_type).to_owned(),
+            license(args.license).to_owned(),
+            image_type(args.image_type).to_owned(),
+            time(args.time).to_owned(),
+            ratio(args.ratio).to_owned(),
+            size(args.size),
+        ]
+        .into_iter()
+        .filter(|filter| !filter.is_empty())
+        .collect();
+
+        // Filters are separated by spaces, and the list starts with one.
+        if !filters.is_empty() {
+            pairs.append_pair("qft", &format!(" {}", filters.join(" ")));
+        }
+
+        // Raw `tbs` values are Google filters, which Bing has no use for.
+        for (key, value) in args.raw_params.iter().filter(|(key, _)| key != "tbs") {
+            pairs.append_pair(key, value);
+        }
+
+        if offset > 0 {
+            pairs.append_pair("first", &(offset + 1).to_string());
+        }
+
+        drop(pairs);
+        url.into()
+    }
+
+    fn parse(&self, body: &str) -> Result<Parsed, ParseStage> {
+        let (images, skipped) = unpack(body)?;
+        if images.is_empty() {
+            return Err(ParseStage::NoEntries);
+        }
+
+        Ok(Parsed {
+            images,
+            skipped,
+            strategy: Strategy::Markup,
+        })
+    }
+}
+
+fn safe_search(safe_search: SafeSearch) -> &'static str {
+    match safe_search {
+        SafeSearch::None => "",
+        SafeSearch::Off => "off",
+        SafeSearch::Blur => "moderate",
+        SafeSearch::Strict => "strict",
+    }
+}
+
+fn color(color: Color) -> &'static str {
+    match color {
+        Color::None => "",
+        Color::Red => "filterui:color2-FGcls_RED",
+        Color::Orange => "filterui:color2-FGcls_ORANGE",
+        Color::Yellow => "filterui:color2-FGcls_YELLOW",
+        Color::Green => "filterui:color2-FGcls_GREEN",
+        Color::Teal => "filterui:color2-FGcls_TEAL",
+        Color::Blue => "filterui:color2-FGcls_BLUE",
+        Color::Purple => "filterui:color2-FGcls_PURPLE",
+        Color::Pink => "filterui:color2-FGcls_PINK",
+        Color::White => "filterui:color2-FGcls_WHITE",
+        Color::Gray => "filterui:color2-FGcls_GRAY",
+        Color::Black => "filterui:color2-FGcls_BLACK",
+        Color::Brown => "filterui:color2-FGcls_BROWN",
+    }
+}
+
+fn color_type(color_type: ColorType) -> &'static str {
+    match color_type {
+        ColorType::None => "",
+        ColorType::Color => "filterui:color2-color",
+        ColorType::Grayscale => "filterui:color2-bw",
+        ColorType::Transparent => "filterui:photo-transparent",
+    }
+}
+
+fn license(license: License) -> &'static str {
+    match license {
+        License::None => "",
+        License::CreativeCommons => "filterui:licenseType-Any",
+        License::Other => "",
+    }
+}
+
+fn image_type(image_type: ImageType) -> &'static str {
+    match image_type {
+        ImageType::None => "",
+        ImageType::Face => "filterui:face-face",
+        ImageType::Photo => "filterui:photo-photo",
+        ImageType::Clipart => "filterui:photo-clipart",
+        ImageType::Lineart => "filterui:photo-linedrawing",
+        ImageType::Animated => "filterui:photo-animatedgif",
+    }
+}
+
+fn time(time: Time) -> &'static str {
+    match time {
+        Time::None => "",
+        Time::Day => "filterui:age-lt1440",
+        Time::Week => "filterui:age-lt10080",
+        Time::Month => "filterui:age-lt43200",
+        Time::Year => "filterui:age-lt525600",
+        Time::Range { .. } => "",
+    }
+}
+
+fn ratio(ratio: Ratio) -> &'static str {
+    match ratio {
+        Ratio::None => "",
+        Ratio::Tall => "filterui:aspect-tall",
+        Ratio::Square => "filterui:aspect-square",
+        Ratio::Wide | Ratio::Panoramic => "filterui:aspect-wide",
+    }
+}
+
+/// Thresholds and exact sizes are both treated as a minimum size, since that is all Bing supports.
+fn size(size: Size) -> String {
+    let (width, height) = match size {
+        Size::None => return String::new(),
+        Size::Large => return String::from("filterui:imagesize-large"),
+        Size::Medium => return String::from("filterui:imagesize-medium"),
+        Size::Icon => return String::from("filterui:imagesize-small"),
+        Size::LargerThan(min) => min.dimensions(),
+        Size::Exact { width, height } => (width, height),
+    };
+
+    format!("filterui:imagesize-custom_{}_{}", width, height)
+}
+
+/// Each result is an `<a class="iusc">` with its info stored as html-escaped json in the `m` attribute.
+/// The dimensions aren't part of that json, but are shown as "width x height" in the `nowrap` span that follows.
+fn unpack(recv: &str) -> Result<(Vec<Image>, usize), ParseStage> {
+    if !recv.contains("class=\"iusc\"") {
+        return Err(ParseStage::BlobNotFound);
+    }
+
+    let mut images: Vec<Image> = Vec::new();
+    let mut skipped = 0;
+    for result in recv.split("class=\"iusc\"").skip(1) {
+        match entry(result) {
+            Some(image) => images.push(image),
+            None => skipped += 1,
+        }
+    }
+
+    Ok((images, skipped))
+}
+
+fn entry(result: &str) -> Option<Image> {
+    let start = result.find(" m=\"")? + " m=\"".len();
+    let end = start + result[start..].find('"')?;
+
+    let json: serde_json::Value = serde_json::from_str(&unescape(&result[start..end])).ok()?;
+    let (width, height) = dimensions(&result[end..]).unwrap_or((0, 0));
+
+    Some(Image {
+        url: json.get("murl")?.as_str()?.to_owned(),
+        width,
+        height,
+        thumbnail: json.get("turl")?.as_str()?.to_owned(),
+        source: json.get("purl")?.as_str()?.to_owned(),
+    })
+}
+
+fn dimensions(result: &str) -> Option<(i64, i64)> {
+    let start = result.find("class=\"nowrap\">")? + "class=\"nowrap\">".len();
+    let info = &result[start..];
+    let info = &info[..info.find('<')?];
+
+    let mut sides = info.split(['x', '×']).map(|side| {
+        side.trim()
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse::<i64>()
+            .ok()
+    });
+
+    Some((sides.next()??, sides.next()??))
+}
+
+fn unescape(attr: &str) -> String {
+    attr.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
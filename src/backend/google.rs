@@ -0,0 +1,278 @@
+use super::{Parsed, SearchBackend, Strategy};
+use crate::{Arguments, Image, ParseStage};
+use serde_json::Value;
+use surf::Url;
+
+/// Searches [Google Images](https://images.google.com). This is the default backend.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, Color, Google, SearchBackend, Time};
+///
+/// let args = Arguments::new("cats & dogs #1", 10);
+/// assert_eq!(
+///     Google.build_url(&args, 0),
+///     "https://www.google.com/search?udm=2&q=cats+%26+dogs+%231"
+/// );
+///
+/// let args = Arguments::new("cats", 10).time(Time::Day);
+/// assert_eq!(
+///     Google.build_url(&args, 0),
+///     "https://www.google.com/search?udm=2&q=cats&tbs=qdr%3Ad"
+/// );
+///
+/// let args = args.color(Color::Red);
+/// assert_eq!(
+///     Google.build_url(&args, 0),
+///     "https://www.google.com/search?udm=2&q=cats&tbs=ic%3Aspecific%2Cisc%3Ared%2Cqdr%3Ad"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Google;
+
+impl SearchBackend for Google {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn build_url(&self, args: &Arguments, offset: usize) -> String {
+        let domain = args
+            .domain
+            .as_deref()
+            .filter(|domain| is_google_domain(domain))
+            .unwrap_or("com");
+        let mut url = Url::parse(&format!("https://www.google.{}/search", domain))
+            .unwrap_or_else(|_| Url::parse("https://www.google.com/search").expect("url is valid"));
+
+        let mut pairs = url.query_pairs_mut();
+        pairs.append_pair("udm", "2").append_pair("q", &args.query);
+
+        if let Some(language) = &args.language {
+            pairs.append_pair("hl", language);
+        }
+        if let Some(region) = &args.region {
+            pairs.append_pair("gl", region);
+        }
+        if let Some(country) = &args.country {
+            pairs.append_pair("cr", &format!("country{}", country));
+        }
+
+        let safe = args.safe_search.param();
+        if !safe.is_empty() {
+            pairs.append_pair("safe", safe);
+        }
+
+        let tbs = args.tbs();
+        if !tbs.is_empty() {
+            pairs.append_pair("tbs", &tbs);
+        }
+
+        for (key, value) in args.raw_params.iter().filter(|(key, _)| key != "tbs") {
+            pairs.append_pair(key, value);
+        }
+
+        if offset > 0 {
+            pairs.append_pair("start", &offset.to_string());
+        }
+
+        drop(pairs);
+        url.into()
+    }
+
+    /// Tries each way Google has been known to embed results in a page, in order:
+    /// the `var m={...}` script blob, then the `AF_initDataCallback` payloads, then the `<img>` tags themselves.
+    ///
+    /// The first strategy to find any images wins.
+    /// If none do, the stage the earliest strategy failed at is returned, since that is the one expected to work.
+    fn parse(&self, body: &str) -> Result<Parsed, ParseStage> {
+        let strategies: [(Strategy, Extractor); 3] = [
+            (Strategy::Script, unpack),
+            (Strategy::InitData, init_data),
+            (Strategy::Markup, markup),
+        ];
+
+        let mut failed = None;
+        for (strategy, parse) in strategies {
+            match parse(body) {
+                Ok((images, skipped)) if !images.is_empty() => {
+                    return Ok(Parsed {
+                        images,
+                        skipped,
+                        strategy,
+                    })
+                }
+                Ok(_) => failed.get_or_insert(ParseStage::NoEntries),
+                Err(stage) => failed.get_or_insert(stage),
+            };
+        }
+
+        Err(failed.unwrap_or(ParseStage::NoEntries))
+    }
+}
+
+/// Extracts the images from a page, along with the number of entries that were skipped because they were malformed.
+type Extractor = fn(&str) -> Result<(Vec<Image>, usize), ParseStage>;
+
+/// Parses the `var m={...}` script blob, which maps ids to `[number, entry]` pairs.
+fn unpack(recv: &str) -> Result<(Vec<Image>, usize), ParseStage> {
+    let start = recv.find("var m={").ok_or(ParseStage::BlobNotFound)? + "var m=".len();
+    let mut body = &recv[start..];
+
+    let script_end = body.find("var a=m").ok_or(ParseStage::ScriptEndNotFound)?;
+    body = &body[..script_end];
+
+    let end = body.rfind(';').ok_or(ParseStage::ScriptEndNotFound)?;
+    body = &body[..end];
+
+    let json: Value = serde_json::from_str(body).map_err(|_| ParseStage::InvalidJson)?;
+
+    let image_objects = json
+        .as_object()
+        .ok_or(ParseStage::InvalidJson)?
+        .values()
+        .filter_map(|list| match list.as_array()?.as_slice() {
+            [id, obj, ..] if id.is_u64() => obj.as_array(),
+            _ => None,
+        });
+
+    let mut images: Vec<Image> = Vec::new();
+    let mut skipped = 0;
+    for obj in image_objects {
+        match entry(obj) {
+            Some(image) => images.push(image),
+            None => skipped += 1,
+        }
+    }
+
+    Ok((images, skipped))
+}
+
+/// Parses the `AF_initDataCallback({key: '...', data: [...], sideChannel: {}})` payloads.
+/// The layout of the data changes often, so every nested array is checked for something shaped like an image entry.
+fn init_data(recv: &str) -> Result<(Vec<Image>, usize), ParseStage> {
+    let mut stage = ParseStage::BlobNotFound;
+    let mut images: Vec<Image> = Vec::new();
+
+    for callback in recv.split("AF_initDataCallback(").skip(1) {
+        let start = match callback.find("data:") {
+            Some(start) => start + "data:".len(),
+            None => continue,
+        };
+        let end = match callback.find(", sideChannel:") {
+            Some(end) if end > start => end,
+            _ => {
+                stage = ParseStage::ScriptEndNotFound;
+                continue;
+            }
+        };
+
+        let json: Value = match serde_json::from_str(&callback[start..end]) {
+            Ok(json) => json,
+            Err(_) => {
+                stage = ParseStage::InvalidJson;
+                continue;
+            }
+        };
+
+        stage = ParseStage::NoEntries;
+        collect_entries(&json, &mut images);
+    }
+
+    if images.is_empty() {
+        Err(stage)
+    } else {
+        Ok((images, 0))
+    }
+}
+
+fn collect_entries(value: &Value, images: &mut Vec<Image>) {
+    match value {
+        Value::Array(list) => match entry(list) {
+            Some(image) => images.push(image),
+            None => {
+                for value in list {
+                    collect_entries(value, images);
+                }
+            }
+        },
+        Value::Object(map) => {
+            for value in map.values() {
+                collect_entries(value, images);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Reads an image entry, which holds the thumbnail at `[2]`, the image at `[3]` and the source page under `[9]["2003"]`.
+fn entry(obj: &[Value]) -> Option<Image> {
+    let image = obj.get(3)?.as_array()?;
+    let thumbnail = obj.get(2)?.as_array()?;
+    let source = obj.get(9)?.get("2003")?.as_array()?;
+
+    Some(Image {
+        url: image.first()?.as_str()?.to_owned(),
+        width: image.get(1)?.as_i64()?,
+        height: image.get(2)?.as_i64()?,
+        thumbnail: thumbnail.first()?.as_str()?.to_owned(),
+        source: source.get(2)?.as_str()?.to_owned(),
+    })
+}
+
+/// Last resort that scrapes the `<img>` tags of the page.
+/// Only the thumbnails can be found this way, so they are used as the url of the image as well.
+fn markup(recv: &str) -> Result<(Vec<Image>, usize), ParseStage> {
+    let mut images: Vec<Image> = Vec::new();
+
+    for tag in recv.split("<img").skip(1) {
+        let tag = match tag.find('>') {
+            Some(end) => &tag[..end],
+            None => continue,
+        };
+
+        let src = match attribute(tag, "data-src").or_else(|| attribute(tag, "src")) {
+            Some(src) if src.starts_with("http") && !src.contains("google.com/") => src,
+            _ => continue,
+        };
+
+        let dimension = |name| {
+            attribute(tag, name)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0)
+        };
+
+        images.push(Image {
+            url: src.replace("&amp;", "&"),
+            width: dimension("width"),
+            height: dimension("height"),
+            thumbnail: src.replace("&amp;", "&"),
+            source: String::new(),
+        });
+    }
+
+    if images.is_empty() {
+        Err(ParseStage::NoEntries)
+    } else {
+        Ok((images, 0))
+    }
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')?;
+
+    Some(&tag[start..start + end])
+}
+
+/// Whether Google can be searched on `domain`, which is `com`, a two letter country code, or `co.` or `com.` followed by one.
+pub(crate) fn is_google_domain(domain: &str) -> bool {
+    let country = |label: &str| label.len() == 2 && label.bytes().all(|b| b.is_ascii_lowercase());
+
+    match domain.split_once('.') {
+        Some((second, code)) => matches!(second, "co" | "com") && country(code),
+        None => domain == "com" || country(domain),
+    }
+}
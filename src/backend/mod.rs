@@ -0,0 +1,100 @@
+//! Search engines that images can be fetched from.
+//!
+//! Google is used unless another backend is selected with [`Arguments::backend`](crate::Arguments::backend).
+//! Other engines can be added by implementing [`SearchBackend`].
+
+mod bing;
+mod google;
+
+pub use bing::Bing;
+pub use google::Google;
+
+pub(crate) use google::is_google_domain;
+
+use crate::{Arguments, Image, ParseStage};
+use std::fmt;
+use std::sync::Arc;
+
+/// A search engine that can be queried for images.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, ParseStage, Parsed, SearchBackend};
+///
+/// #[derive(Debug)]
+/// struct Archive;
+///
+/// impl SearchBackend for Archive {
+///     fn name(&self) -> &'static str {
+///         "archive"
+///     }
+///
+///     fn build_url(&self, args: &Arguments, offset: usize) -> String {
+///         format!("https://archive.example.com/search?q={}&skip={}", args.get_query(), offset)
+///     }
+///
+///     fn parse(&self, body: &str) -> Result<Parsed, ParseStage> {
+///         Err(ParseStage::BlobNotFound)
+///     }
+/// }
+///
+/// let args = Arguments::new("example", 10).backend(Archive);
+/// ```
+pub trait SearchBackend: fmt::Debug + Send + Sync {
+    /// A short, lowercase name for the backend, such as `"google"`.
+    fn name(&self) -> &'static str;
+
+    /// Builds the url for the page of results starting at `offset`, the number of results already seen.
+    fn build_url(&self, args: &Arguments, offset: usize) -> String;
+
+    /// Parses the images out of a page of results.
+    /// Returns the stage parsing failed at if the page is not in the format the backend expects, or has no images.
+    ///
+    /// This must not panic on any input, since the page is whatever the server decided to send back.
+    /// Malformed results should be skipped and counted in [`Parsed::skipped`] instead.
+    fn parse(&self, body: &str) -> Result<Parsed, ParseStage>;
+}
+
+/// Finds one of the backends included in this crate by its [`name`](SearchBackend::name), such as `"bing"`.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::backend;
+///
+/// assert_eq!(backend::by_name("bing").unwrap().name(), "bing");
+/// assert!(backend::by_name("altavista").is_none());
+/// ```
+pub fn by_name(name: &str) -> Option<Arc<dyn SearchBackend>> {
+    match name.trim().to_lowercase().as_str() {
+        "google" => Some(Arc::new(Google)),
+        "bing" => Some(Arc::new(Bing)),
+        _ => None,
+    }
+}
+
+/// The images parsed from a page of results, along with how they were found.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub images: Vec<Image>,
+    /// The number of results that were skipped because they were malformed or missing information.
+    pub skipped: usize,
+    pub strategy: Strategy,
+}
+
+/// The way images were extracted from a page of results.
+///
+/// Search engines change their markup without warning, so keeping track of which strategy is being used
+/// helps notice when the preferred one stops working before the fallbacks do as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Read from the data embedded in a script on the page.
+    Script,
+    /// Read from the payloads passed to `AF_initDataCallback`.
+    InitData,
+    /// Scraped from the html elements of the page.
+    Markup,
+}
@@ -0,0 +1,127 @@
+use crate::{download_with, Arguments, DownloadReport, Downloader, Politeness, SearchResult};
+use futures::future;
+
+/// A set of queries to download together, sharing one client and one set of limits.
+///
+/// Downloading queries one at a time with [`download`](crate::download) gives each of them its own client,
+/// and nothing stops several of them from flooding the same hosts at once.
+/// A batch runs every query concurrently, but counts all of their requests, searches included, against the same limits.
+///
+/// # Example
+/// ```no_run
+/// extern crate tokio;
+/// extern crate image_search;
+///
+/// use image_search::{download_batch, Arguments, Batch};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let batch = Batch::new(vec![Arguments::new("cats", 10), Arguments::new("dogs", 10)])
+///         .concurrency(8)
+///         .rate_limit(4);
+///
+///     for report in download_batch(batch).await {
+///         match report.result {
+///             Ok(download) => println!("{}: {} images", report.query, download.paths().len()),
+///             Err(err) => eprintln!("{}: {}", report.query, err),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Batch {
+    queries: Vec<Arguments>,
+    concurrency: usize,
+    host_concurrency: usize,
+    rate_limit: Option<u32>,
+    politeness: Politeness,
+}
+
+impl Batch {
+    /// Creates a batch of `queries`, making up to 16 requests at once and 4 to the same host,
+    /// with no overall rate limit and the default [`Politeness`] towards each host.
+    pub fn new(queries: Vec<Arguments>) -> Batch {
+        Batch {
+            queries,
+            concurrency: 16,
+            host_concurrency: 4,
+            rate_limit: None,
+            politeness: Politeness::new(),
+        }
+    }
+
+    /// Sets the most requests that can be made at once across every query in the batch. Defaults to 16.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the most requests that can be made to the same host at once across every query in the batch. Defaults to 4.
+    ///
+    /// This replaces the [`host_concurrency`](Arguments::host_concurrency) of each query,
+    /// while their [`concurrency`](Arguments::concurrency) still limits how many of their own images are downloaded at once.
+    pub fn host_concurrency(mut self, host_concurrency: usize) -> Self {
+        self.host_concurrency = host_concurrency.max(1);
+        self
+    }
+
+    /// Sets the most requests that can be started every second across every query in the batch. Not limited by default.
+    pub fn rate_limit(mut self, per_second: u32) -> Self {
+        self.rate_limit = Some(per_second.max(1));
+        self
+    }
+
+    /// Sets the rate limit and crawl delay for each image host across every query in the batch. Defaults to [`Politeness::new`].
+    ///
+    /// This replaces the [`politeness`](Arguments::politeness) of each query, since they all download from the same hosts.
+    pub fn politeness(mut self, politeness: Politeness) -> Self {
+        self.politeness = politeness;
+        self
+    }
+}
+
+impl From<Vec<Arguments>> for Batch {
+    fn from(queries: Vec<Arguments>) -> Self {
+        Batch::new(queries)
+    }
+}
+
+/// The outcome of one query in a [`Batch`].
+#[derive(Debug)]
+pub struct QueryReport {
+    /// The query, as it was given to [`Arguments::new`].
+    pub query: String,
+    /// What happened to each image, or the reason the query failed.
+    pub result: SearchResult<DownloadReport>,
+}
+
+/// Downloads the images for every query in a batch, returning a report for each query in the order they were given.
+///
+/// A query that fails doesn't stop the others, its error is returned in its report instead.
+pub async fn download_batch<B: Into<Batch>>(batch: B) -> Vec<QueryReport> {
+    async_std::task::spawn(_download_batch(batch.into())).await
+}
+
+/// Downloads the images for every query in a batch, returning a report for each query in the order they were given.
+///
+/// Must be called with [async_std::task::spawn] or with a [Tokio 0.2.x runtime](https://crates.io/crates/tokio/0.2.25).
+pub(crate) async fn _download_batch(batch: Batch) -> Vec<QueryReport> {
+    let downloader = Downloader::new(
+        Some(batch.concurrency),
+        Some(batch.host_concurrency),
+        batch.rate_limit,
+        batch.politeness,
+    );
+
+    let downloads = batch.queries.into_iter().map(|args| {
+        let downloader = &downloader;
+        async move {
+            QueryReport {
+                query: args.query.clone(),
+                result: download_with(downloader, args).await,
+            }
+        }
+    });
+
+    future::join_all(downloads).await
+}
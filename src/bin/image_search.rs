@@ -0,0 +1,439 @@
+//! Searches for and downloads images from the command line. Built with the `cli` feature.
+
+extern crate clap;
+extern crate image_search;
+extern crate serde_json;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use image_search::{
+    blocking, Arguments, Bing, Color, ColorType, Error, Format, Google, ImageType, License, Ratio,
+    SafeSearch, Size, Time,
+};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  Success
+  2  Invalid command line
+  3  Invalid arguments, such as conflicting filters
+  4  Network error
+  5  The results could not be parsed
+  6  Blocked by a consent page
+  7  Blocked by a CAPTCHA
+  8  Rate limited
+  9  Unable to create or write to the directory";
+
+#[derive(Parser)]
+#[command(version, about, after_help = EXIT_CODES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Searches for images and prints their info.
+    Search(Search),
+    /// Searches for images and prints their urls.
+    Urls(Search),
+    /// Searches for images and downloads them, printing where each one was saved or why it wasn't.
+    Download(Search),
+    /// Prints the arguments the flags describe as JSON, without searching.
+    Arguments(Search),
+}
+
+#[derive(Args)]
+struct Search {
+    /// The text to search for.
+    #[arg(required_unless_present = "url")]
+    query: Option<String>,
+
+    /// Reads the query and filters from a Google Images url, which the other flags override.
+    #[arg(long, conflicts_with = "query")]
+    url: Option<String>,
+
+    /// The maximum amount of images to fetch.
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+
+    /// How to print the results.
+    #[arg(short, long, value_enum, default_value_t = Output::Table)]
+    output: Output,
+
+    /// Uses the thumbnails instead of the full size images.
+    #[arg(long)]
+    thumbnails: bool,
+
+    /// The request timeout for downloads, in seconds. 0 disables it.
+    #[arg(long, value_name = "SECS", value_parser = seconds)]
+    timeout: Option<Duration>,
+
+    /// The directory images are downloaded to.
+    #[arg(long, value_name = "DIR")]
+    dir: Option<PathBuf>,
+
+    /// Saves pages of results that can't be parsed to this path.
+    #[arg(long, value_name = "PATH")]
+    snapshot: Option<PathBuf>,
+
+    /// The most images to download at once.
+    #[arg(long, value_name = "N")]
+    concurrency: Option<usize>,
+
+    /// The most images to download at once from the same host.
+    #[arg(long, value_name = "N")]
+    host_concurrency: Option<usize>,
+
+    /// The most images to download every second from the same host. 0 removes the limit.
+    #[arg(long, value_name = "PER_SEC")]
+    host_rate: Option<f64>,
+
+    /// The least time between downloads from the same host, in seconds.
+    #[arg(long, value_name = "SECS", value_parser = seconds)]
+    crawl_delay: Option<Duration>,
+
+    /// The most times each request is made before giving up on it. 1 disables retrying.
+    #[arg(long, value_name = "N")]
+    attempts: Option<u32>,
+
+    /// Only downloads images that robots.txt allows this user agent to fetch.
+    #[arg(long, value_name = "USER_AGENT")]
+    robots: Option<String>,
+
+    /// The search engine to use. Can be given more than once to search several engines.
+    #[arg(short, long, value_enum)]
+    backend: Vec<Backend>,
+
+    /// red, orange, yellow, green, teal, blue, purple, pink, white, gray, black or brown.
+    #[arg(long)]
+    color: Option<Color>,
+
+    /// color, grayscale or transparent.
+    #[arg(long)]
+    color_type: Option<ColorType>,
+
+    /// creative_commons or other.
+    #[arg(long)]
+    license: Option<License>,
+
+    /// face, photo, clipart, lineart or animated.
+    #[arg(long = "type", value_name = "TYPE")]
+    image_type: Option<ImageType>,
+
+    /// day, week, month, year, or a range of dates like 2024-07-26..2024-08-11.
+    #[arg(long)]
+    time: Option<Time>,
+
+    /// tall, square, wide or panoramic.
+    #[arg(long)]
+    ratio: Option<Ratio>,
+
+    /// jpg, gif, png, bmp, svg, webp, ico or raw.
+    #[arg(long)]
+    format: Option<Format>,
+
+    /// large, medium, icon, a minimum like larger_than_4mp, or exact dimensions like 1920x1080.
+    #[arg(long)]
+    size: Option<Size>,
+
+    /// The country domain of Google to search, such as co.uk.
+    #[arg(long)]
+    domain: Option<String>,
+
+    /// The language of the results page, such as en.
+    #[arg(long)]
+    language: Option<String>,
+
+    /// The region the search appears to come from, such as us.
+    #[arg(long)]
+    region: Option<String>,
+
+    /// Only finds images from sites in a country, such as US.
+    #[arg(long)]
+    country: Option<String>,
+
+    /// off, blur or strict.
+    #[arg(long)]
+    safe_search: Option<SafeSearch>,
+
+    /// Adds a parameter to the search url as is. Can be given more than once.
+    #[arg(long, value_name = "KEY=VALUE", value_parser = raw_param)]
+    raw: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    /// Aligned columns.
+    Table,
+    /// A JSON array.
+    Json,
+    /// One url or path per line.
+    Plain,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Backend {
+    Google,
+    Bing,
+}
+
+/// Reads a number of seconds, which can't be negative or too large for a [`Duration`].
+fn seconds(secs: &str) -> Result<Duration, String> {
+    let secs: f64 = secs
+        .parse()
+        .map_err(|_| String::from("expected a number of seconds"))?;
+    Duration::try_from_secs_f64(secs).map_err(|err| err.to_string())
+}
+
+fn raw_param(param: &str) -> Result<(String, String), String> {
+    match param.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(String::from("expected KEY=VALUE")),
+    }
+}
+
+impl Search {
+    /// The arguments the flags describe.
+    fn arguments(&self) -> Result<Arguments, Error> {
+        let mut args = match &self.url {
+            Some(url) => {
+                let (args, unknown) = Arguments::from_url(url)?;
+                for param in unknown {
+                    eprintln!("warning: passing on unsupported parameter {}", param);
+                }
+                args.limit(self.limit)
+            }
+            None => Arguments::new(self.query.clone().unwrap_or_default(), self.limit),
+        };
+
+        args = args.thumbnails(self.thumbnails);
+        if let Some(timeout) = self.timeout {
+            args = args.timeout((!timeout.is_zero()).then_some(timeout));
+        }
+        if let Some(dir) = &self.dir {
+            args = args.directory(dir);
+        }
+        if let Some(snapshot) = &self.snapshot {
+            args = args.snapshot(snapshot);
+        }
+        if let Some(concurrency) = self.concurrency {
+            args = args.concurrency(concurrency);
+        }
+        if let Some(host_concurrency) = self.host_concurrency {
+            args = args.host_concurrency(host_concurrency);
+        }
+        if let Some(rate) = self.host_rate {
+            let politeness = args.get_politeness().clone().rate_limit(rate);
+            args = args.politeness(politeness);
+        }
+        if let Some(delay) = self.crawl_delay {
+            let politeness = args.get_politeness().clone().crawl_delay(delay);
+            args = args.politeness(politeness);
+        }
+        if let Some(attempts) = self.attempts {
+            let retry = args.get_retry().clone().attempts(attempts);
+            args = args.retry(retry);
+        }
+        if let Some(user_agent) = &self.robots {
+            args = args.robots(user_agent);
+        }
+        for (i, backend) in self.backend.iter().enumerate() {
+            args = match (i, backend) {
+                (0, Backend::Google) => args.backend(Google),
+                (0, Backend::Bing) => args.backend(Bing),
+                (_, Backend::Google) => args.add_backend(Google),
+                (_, Backend::Bing) => args.add_backend(Bing),
+            };
+        }
+
+        if let Some(color) = self.color {
+            args = args.color(color);
+        }
+        if let Some(color_type) = self.color_type {
+            args = args.color_type(color_type);
+        }
+        if let Some(license) = self.license {
+            args = args.license(license);
+        }
+        if let Some(image_type) = self.image_type {
+            args = args.image_type(image_type);
+        }
+        if let Some(time) = self.time {
+            args = args.time(time);
+        }
+        if let Some(ratio) = self.ratio {
+            args = args.ratio(ratio);
+        }
+        if let Some(format) = self.format {
+            args = args.format(format);
+        }
+        if let Some(size) = self.size {
+            args = args.size(size);
+        }
+
+        if let Some(domain) = &self.domain {
+            args = args.domain(domain);
+        }
+        if let Some(language) = &self.language {
+            args = args.language(language);
+        }
+        if let Some(region) = &self.region {
+            args = args.region(region);
+        }
+        if let Some(country) = &self.country {
+            args = args.country(country);
+        }
+        if let Some(safe_search) = self.safe_search {
+            args = args.safe_search(safe_search);
+        }
+        for (key, value) in &self.raw {
+            args = args.raw_param(key, value);
+        }
+
+        Ok(args)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Search(search) => {
+            let images = blocking::search(search.arguments()?)?;
+
+            match search.output {
+                Output::Json => print_json(&images),
+                Output::Plain => images.iter().for_each(|image| println!("{}", image.url)),
+                Output::Table => {
+                    let rows: Vec<[String; 3]> = images
+                        .iter()
+                        .map(|image| {
+                            [
+                                format!("{}x{}", image.width, image.height),
+                                image.url.clone(),
+                                image.source.clone(),
+                            ]
+                        })
+                        .collect();
+                    print_table(["SIZE", "URL", "SOURCE"], &rows);
+                }
+            }
+        }
+        Command::Urls(search) => {
+            let urls = blocking::urls(search.arguments()?)?;
+
+            match search.output {
+                Output::Json => print_json(&urls),
+                Output::Plain | Output::Table => urls.iter().for_each(|url| println!("{}", url)),
+            }
+        }
+        Command::Download(search) => {
+            let report = blocking::download(search.arguments()?)?;
+
+            match search.output {
+                Output::Json => {
+                    let images: Vec<serde_json::Value> = report
+                        .images
+                        .iter()
+                        .map(|image| {
+                            serde_json::json!({
+                                "url": image.image.url,
+                                "path": image.result.as_ref().ok(),
+                                "error": image.result.as_ref().err().map(ToString::to_string),
+                                "bytes": image.bytes,
+                                "seconds": image.duration.as_secs_f64(),
+                                "attempts": image.attempts,
+                            })
+                        })
+                        .collect();
+                    print_json(&images);
+                }
+                Output::Plain => {
+                    for image in &report.images {
+                        match &image.result {
+                            Ok(path) => println!("{}", path.display()),
+                            Err(err) => eprintln!("warning: {}: {}", image.image.url, err),
+                        }
+                    }
+                }
+                Output::Table => {
+                    let rows: Vec<[String; 4]> = report
+                        .images
+                        .iter()
+                        .map(|image| {
+                            [
+                                match &image.result {
+                                    Ok(path) => path.display().to_string(),
+                                    Err(err) => err.to_string(),
+                                },
+                                image.bytes.to_string(),
+                                format!("{:.2}s", image.duration.as_secs_f64()),
+                                image.image.url.clone(),
+                            ]
+                        })
+                        .collect();
+                    print_table(["RESULT", "BYTES", "TIME", "URL"], &rows);
+                }
+            }
+        }
+        Command::Arguments(search) => print_json(&search.arguments()?),
+    }
+
+    Ok(())
+}
+
+/// The code the tool exits with after failing with `err`, as listed in [`EXIT_CODES`].
+fn exit_code(err: &Error) -> u8 {
+    match err {
+        Error::Arguments(_) => 3,
+        Error::Network(_) => 4,
+        Error::Parse(_) => 5,
+        Error::Consent => 6,
+        Error::Captcha => 7,
+        Error::RateLimited { .. } => 8,
+        Error::Dir(_) => 9,
+    }
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(err) => eprintln!("error: {}", err),
+    }
+}
+
+/// Prints the rows in columns as wide as their widest cell, leaving the last column unpadded.
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: [&str; N]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(header);
+    for row in rows {
+        print_row(row.each_ref().map(String::as_str));
+    }
+}
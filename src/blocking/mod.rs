@@ -3,30 +3,20 @@
 
 extern crate async_std;
 extern crate futures;
-extern crate glob;
-extern crate infer;
-extern crate serde_json;
-extern crate surf;
 
-use crate::{get, Arguments, DownloadError, Error, Image, SearchResult};
-use futures::future;
-use std::env;
-use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use crate::{Arguments, Batch, DownloadReport, Image, QueryReport, SearchResult};
 
 /// Search for images based on the provided arguments and return images up to the provided limit.
 ///
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// extern crate image_search;
 ///
 /// use image_search::Arguments;
@@ -39,17 +29,39 @@ use std::time::Duration;
 ///     Ok(())
 /// }
 pub fn search(args: Arguments) -> SearchResult<Vec<Image>> {
-    let url = crate::build_url(&args);
-
-    let body = async_std::task::block_on(get(url))?;
-
-    let imgs = crate::unpack(body).ok_or(Error::Parse)?;
+    async_std::task::block_on(crate::_search(args))
+}
 
-    if imgs.len() > args.limit && args.limit > 0 {
-        Ok(imgs[..args.limit].to_vec())
-    } else {
-        Ok(imgs)
-    }
+/// Search for images based on the provided arguments and yield them as each page of results is parsed.
+///
+/// This is the blocking counterpart of [`crate::search_stream`], and only blocks when the next page of results needs to be fetched.
+///
+/// # Errors
+/// The iterator will yield an error and end if, on any page:
+/// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
+/// * The images are not able to be parsed, unless the page is past the last result and has none
+///
+/// # Examples
+///
+/// ```no_run
+/// extern crate image_search;
+///
+/// use image_search::Arguments;
+/// use image_search::blocking::search_iter;
+///
+/// fn main() -> Result<(), image_search::Error> {
+///     let args = Arguments::new("cats", 500);
+///
+///     for image in search_iter(args).take(20) {
+///         println!("{}", image?.url);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn search_iter(args: Arguments) -> impl Iterator<Item = SearchResult<Image>> {
+    futures::executor::block_on_stream(Box::pin(crate::search_stream(args)))
 }
 
 /// Search for images based on the provided arguments and return the urls of the images
@@ -57,11 +69,12 @@ pub fn search(args: Arguments) -> SearchResult<Vec<Image>> {
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// extern crate image_search;
 ///
 /// use image_search::Arguments;
@@ -74,7 +87,7 @@ pub fn search(args: Arguments) -> SearchResult<Vec<Image>> {
 ///     Ok(())
 /// }
 pub fn urls(args: Arguments) -> SearchResult<Vec<String>> {
-    let thumbnails = (&args.thumbnails).to_owned();
+    let thumbnails = args.thumbnails;
     let images = search(args)?;
 
     let mut all: Vec<String> = Vec::new();
@@ -93,13 +106,14 @@ pub fn urls(args: Arguments) -> SearchResult<Vec<String>> {
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 /// * The program is unable to create/read/write to files or directories
 ///
 /// # Examples
 ///
-/// ```
-/// extern crate image_search
+/// ```no_run
+/// extern crate image_search;
 ///
 /// use image_search::Arguments;
 /// use image_search::blocking::download;
@@ -107,166 +121,32 @@ pub fn urls(args: Arguments) -> SearchResult<Vec<String>> {
 ///
 /// fn main() -> Result<(), image_search::Error> {
 ///     let args = Arguments::new("cats", 10).directory(Path::new("downloads"));
-///     let paths = download(args)?;
+///     let report = download(args)?;
+///     println!("saved {} of 10 images", report.paths().len());
 ///
 ///     Ok(())
 /// }
-pub fn download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
-    let images = urls(Arguments {
-        query: args.query.clone(),
-        limit: 0,
-        directory: args.directory.clone(),
-        ..args
-    })?;
-
-    let dir = match args.directory {
-        Some(dir) => dir.to_owned(),
-        None => match env::current_dir() {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Dir(e)),
-        }
-        .join("images"),
-    };
-
-    match std::fs::create_dir_all(&dir) {
-        Ok(_) => (),
-        Err(e) => return Err(Error::Dir(e)),
-    };
-
-    let mut suffix = 0;
-    let mut paths: Vec<PathBuf> = Vec::new();
-    for _ in 0..args.limit {
-        let mut path = dir.join(args.query.to_owned() + &suffix.to_string());
-
-        let mut matches = match glob::glob(&(path.display().to_string() + ".*")) {
-            Ok(paths) => paths.last().is_some(),
-            Err(_) => false,
-        };
-
-        while matches {
-            suffix += 1;
-            path = dir.join(args.query.to_owned() + &suffix.to_string());
-            matches = match glob::glob(&(path.display().to_string() + ".*")) {
-                Ok(paths) => paths.last().is_some(),
-                Err(_) => false,
-            };
-        }
-
-        paths.push(path);
-        suffix += 1;
-    }
-
-    let with_extensions = async_std::task::block_on(download_n(images, paths, args.timeout));
-
-    Ok(with_extensions)
-}
-
-/// Downloads up to n images concurrently
-async fn download_n(
-    urls: Vec<String>,
-    paths: Vec<PathBuf>,
-    timeout: Option<Duration>,
-) -> Vec<PathBuf> {
-    let mut_urls = Arc::new(Mutex::new(urls));
-
-    let mut downloaders = Vec::new();
-    let client = surf::Client::new();
-    for path in paths {
-        downloaders.push(download_until(
-            mut_urls.clone(),
-            path,
-            client.clone(),
-            timeout,
-        ));
-    }
-
-    let with_extensions = future::join_all(downloaders)
-        .await
-        .into_iter()
-        .filter_map(|x| x.ok())
-        .collect();
-
-    with_extensions
-}
-
-macro_rules! next_available {
-    ($urls:expr) => {{
-        let mut mut_urls = $urls.lock().unwrap(); // Safe: no thread should panic while holding, since this is the only unwrap/expect
-        if mut_urls.is_empty() {
-            return Err(DownloadError::Overflow);
-        }
-        let url = mut_urls.remove(0);
-        std::mem::drop(mut_urls);
-
-        url
-    }};
+pub fn download(args: Arguments) -> SearchResult<DownloadReport> {
+    async_std::task::block_on(crate::_download(args))
 }
 
-/// Trys to download an image to a given path until one is successful or it runs out of possible urls
-async fn download_until(
-    urls: Arc<Mutex<Vec<String>>>,
-    path: PathBuf,
-    client: surf::Client,
-    timeout: Option<Duration>,
-) -> Result<PathBuf, DownloadError> {
-    let mut url = next_available!(urls);
-
-    let with_extension = loop {
-        let with_extension = download_image(client.clone(), &path, url.to_owned(), timeout).await;
-        if with_extension.is_ok() {
-            break with_extension;
-        }
-        url = next_available!(urls);
-    };
-
-    with_extension
-}
-
-async fn download_image(
-    client: surf::Client,
-    path: &PathBuf,
-    url: String,
-    timeout: Option<Duration>,
-) -> Result<PathBuf, DownloadError> {
-    let buf = match timeout {
-        Some(duration) => {
-            async_std::future::timeout(duration, client.recv_bytes(surf::get(url))).await?
-        }
-        None => client.recv_bytes(surf::get(url)).await,
-    }?;
-
-    let first_128 = buf.iter().take(1024).map(|x| *x).collect::<Vec<u8>>();
-    let svg = match std::str::from_utf8(&first_128) {
-        Ok(s) => s.contains("<svg"),
-        Err(_) => false,
-    };
-
-    let extension = if svg {
-        "svg".to_owned()
-    } else {
-        let kind = match infer::get(&buf) {
-            Some(k) => k,
-            None => return Err(DownloadError::Extension),
-        };
-
-        if kind.matcher_type() != infer::MatcherType::Image {
-            return Err(DownloadError::Extension);
-        }
-
-        kind.extension().to_owned()
-    };
-
-    let with_extension = path.clone().with_extension(extension);
-
-    let mut f = match File::create(&with_extension) {
-        Ok(f) => f,
-        Err(e) => return Err(DownloadError::Fs(e)),
-    };
-
-    match f.write_all(&buf) {
-        Ok(_) => (),
-        Err(e) => return Err(DownloadError::Fs(e)),
-    };
-
-    Ok(with_extension)
+/// Downloads the images for every query in a batch, returning a report for each query in the order they were given.
+///
+/// A query that fails doesn't stop the others, its error is returned in its report instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, Batch};
+/// use image_search::blocking::download_batch;
+///
+/// let batch = Batch::new(vec![Arguments::new("cats", 10), Arguments::new("dogs", 10)]).concurrency(8);
+/// for report in download_batch(batch) {
+///     println!("{}: {:?}", report.query, report.result);
+/// }
+/// ```
+pub fn download_batch<B: Into<Batch>>(batch: B) -> Vec<QueryReport> {
+    async_std::task::block_on(crate::batch::_download_batch(batch.into()))
 }
@@ -0,0 +1,514 @@
+//! Loading batches of [`Arguments`] from TOML or JSON job files, enabled with the `jobs` feature.
+//!
+//! A job file has a `defaults` table that applies to every query, and a list of `queries` that can override any of the defaults.
+//! Each query is either a table with the same keys as the defaults, or just the text to search for.
+//!
+//! Keys are named after the [`Arguments`] builder methods, filters are written the same as their `Display` implementations,
+//! `timeout` is in seconds, `backends` is a list of names, and `raw_params` is a table of extra url parameters.
+//! `politeness` is a table with any of `rate_limit` per second, `burst`, and `crawl_delay` in seconds.
+//! `retry` is a table with any of `attempts`, `backoff` and `max_backoff` in seconds, `jitter`, and `retry_on`, a list of failure kinds.
+//! A query's `politeness` and `retry` tables only change the keys they set, and are checked the same way as the builders:
+//! `rate_limit` can't be negative, `burst` and `attempts` are at least 1, and `max_backoff` is at least `backoff`.
+//!
+//! # Example
+//! ```
+//! extern crate image_search;
+//!
+//! use image_search::jobs;
+//!
+//! let job = r#"
+//! [defaults]
+//! limit = 50
+//! directory = "images"
+//! color_type = "transparent"
+//!
+//! [[queries]]
+//! query = "cats"
+//! time = "week"
+//!
+//! [[queries]]
+//! query = "dogs"
+//! limit = 10
+//! backends = ["bing"]
+//! "#;
+//!
+//! let args = jobs::from_toml(job).unwrap();
+//! assert_eq!(args.len(), 2);
+//! assert_eq!(args[0].get_limit(), 50);
+//! assert_eq!(args[1].get_limit(), 10);
+//!
+//! let err = jobs::from_json(r#"{"queries": ["cats", {"query": "dogs", "color": "mauve"}]}"#).unwrap_err();
+//! assert_eq!(err.to_string(), r#"line 1, column 56: queries[1].color: "mauve" is not a valid color"#);
+//! ```
+
+use crate::backend::SearchBackend;
+use crate::{
+    serialize, Arguments, Color, ColorType, FailureKind, Format, ImageType, License, Politeness,
+    Ratio, RetryPolicy, SafeSearch, Size, Time,
+};
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Reads a job file, picking the format from its extension, which is either `.toml` or `.json`.
+///
+/// # Errors
+/// This function will return an error if:
+/// * The file can't be read
+/// * The extension isn't `.toml` or `.json`
+/// * The job is invalid, in which case the error points at the line and key that caused it
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Arguments>, JobError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    match extension.as_deref() {
+        Some("toml") => from_toml(&fs::read_to_string(path)?),
+        Some("json") => from_json(&fs::read_to_string(path)?),
+        _ => Err(JobError::Extension(path.to_owned())),
+    }
+}
+
+/// Reads a job written in TOML.
+///
+/// # Errors
+/// This function will return an error if the job is invalid, pointing at the line and key that caused it.
+pub fn from_toml(job: &str) -> Result<Vec<Arguments>, JobError> {
+    let job: Job =
+        serde_path_to_error::deserialize(toml::Deserializer::new(job)).map_err(|err| {
+            let key = key(err.path());
+            let err = err.into_inner();
+            let (line, column) = match err.span() {
+                Some(span) => position(job, span.start),
+                None => (None, None),
+            };
+
+            JobError::Invalid {
+                line,
+                column,
+                key,
+                message: err.message().trim().to_owned(),
+            }
+        })?;
+
+    job.build()
+}
+
+/// Reads a job written in JSON.
+///
+/// # Errors
+/// This function will return an error if the job is invalid, pointing at the line and key that caused it.
+pub fn from_json(job: &str) -> Result<Vec<Arguments>, JobError> {
+    let mut deserializer = serde_json::Deserializer::from_str(job);
+    let invalid = |key: String, err: serde_json::Error| {
+        // The position is reported separately, so it's taken off the end of the message.
+        let message = err.to_string();
+        let suffix = format!(" at line {} column {}", err.line(), err.column());
+
+        JobError::Invalid {
+            line: Some(err.line()),
+            column: Some(err.column()),
+            key,
+            message: message.strip_suffix(&suffix).unwrap_or(&message).to_owned(),
+        }
+    };
+
+    let job: Job = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| invalid(key(err.path()), err.into_inner()))?;
+    deserializer
+        .end()
+        .map_err(|err| invalid(String::new(), err))?;
+
+    job.build()
+}
+
+/// The key an error happened at, which is empty for the top of the file.
+fn key(path: &serde_path_to_error::Path) -> String {
+    let key = path.to_string();
+    if key == "." {
+        String::new()
+    } else {
+        key
+    }
+}
+
+/// The line and column of a byte offset, both starting at 1.
+fn position(text: &str, offset: usize) -> (Option<usize>, Option<usize>) {
+    let before = match text.get(..offset) {
+        Some(before) => before,
+        None => return (None, None),
+    };
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+    (
+        Some(before.matches('\n').count() + 1),
+        Some(before[line_start..].chars().count() + 1),
+    )
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    #[serde(default)]
+    defaults: Overrides,
+    queries: Vec<Entry>,
+}
+
+impl Job {
+    fn build(&self) -> Result<Vec<Arguments>, JobError> {
+        self.queries
+            .iter()
+            .enumerate()
+            .map(|(i, Entry(overrides))| {
+                let invalid = |key: &str, message: String| JobError::Invalid {
+                    line: None,
+                    column: None,
+                    key: format!("queries[{}]{}", i, key),
+                    message,
+                };
+                let args =
+                    self.defaults
+                        .apply(serialize::defaults())
+                        .map_err(|(key, message)| JobError::Invalid {
+                            line: None,
+                            column: None,
+                            key: format!("defaults{}", key),
+                            message,
+                        })?;
+                let args = overrides
+                    .apply(args)
+                    .map_err(|(key, message)| invalid(key, message))?;
+                if args.query.trim().is_empty() {
+                    return Err(invalid(
+                        ".query",
+                        String::from("every query needs text to search for"),
+                    ));
+                }
+                args.validate()
+                    .map_err(|err| invalid("", err.to_string()))?;
+
+                Ok(args)
+            })
+            .collect()
+    }
+}
+
+/// A query, which can be written as a table of arguments or as just the text to search for.
+struct Entry(Overrides);
+
+impl<'de> Deserialize<'de> for Entry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = Entry;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("the text to search for, or a table of arguments")
+            }
+
+            fn visit_str<E: de::Error>(self, query: &str) -> Result<Entry, E> {
+                Ok(Entry(Overrides {
+                    query: Some(query.to_owned()),
+                    ..Overrides::default()
+                }))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Entry, A::Error> {
+                Overrides::deserialize(de::value::MapAccessDeserializer::new(map)).map(Entry)
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+/// The arguments set by the defaults or by a query. Anything left out keeps the value it had before.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Overrides {
+    query: Option<String>,
+    limit: Option<usize>,
+    thumbnails: Option<bool>,
+    timeout: Option<Seconds>,
+    directory: Option<PathBuf>,
+    backends: Option<Backends>,
+    snapshot: Option<PathBuf>,
+    concurrency: Option<usize>,
+    host_concurrency: Option<usize>,
+    politeness: Option<PolitenessTable>,
+    retry: Option<RetryTable>,
+    robots: Option<String>,
+
+    color: Option<Color>,
+    color_type: Option<ColorType>,
+    license: Option<License>,
+    image_type: Option<ImageType>,
+    time: Option<Time>,
+    ratio: Option<Ratio>,
+    format: Option<Format>,
+    size: Option<Size>,
+
+    domain: Option<String>,
+    language: Option<String>,
+    region: Option<String>,
+    country: Option<String>,
+    safe_search: Option<SafeSearch>,
+
+    #[serde(default)]
+    raw_params: BTreeMap<String, String>,
+}
+
+impl Overrides {
+    /// Sets the arguments that were given on top of `args`,
+    /// or returns the key of a value the builders would have to change and why.
+    fn apply(&self, mut args: Arguments) -> Result<Arguments, (&'static str, String)> {
+        if let Some(query) = &self.query {
+            args.query = query.clone();
+        }
+        if let Some(limit) = self.limit {
+            args = args.limit(limit);
+        }
+        if let Some(thumbnails) = self.thumbnails {
+            args = args.thumbnails(thumbnails);
+        }
+        if let Some(Seconds(timeout)) = self.timeout {
+            args = args.timeout(timeout);
+        }
+        if let Some(directory) = &self.directory {
+            args = args.directory(directory);
+        }
+        if let Some(Backends(backends)) = &self.backends {
+            args.backends = backends.clone();
+        }
+        if let Some(snapshot) = &self.snapshot {
+            args = args.snapshot(snapshot);
+        }
+        if let Some(concurrency) = self.concurrency {
+            args = args.concurrency(concurrency);
+        }
+        if let Some(host_concurrency) = self.host_concurrency {
+            args = args.host_concurrency(host_concurrency);
+        }
+        if let Some(politeness) = &self.politeness {
+            let politeness = politeness.apply(args.get_politeness().clone())?;
+            args = args.politeness(politeness);
+        }
+        if let Some(retry) = &self.retry {
+            let retry = retry.apply(args.get_retry().clone())?;
+            args = args.retry(retry);
+        }
+        if let Some(user_agent) = &self.robots {
+            args = args.robots(user_agent);
+        }
+
+        if let Some(color) = self.color {
+            args = args.color(color);
+        }
+        if let Some(color_type) = self.color_type {
+            args = args.color_type(color_type);
+        }
+        if let Some(license) = self.license {
+            args = args.license(license);
+        }
+        if let Some(image_type) = self.image_type {
+            args = args.image_type(image_type);
+        }
+        if let Some(time) = self.time {
+            args = args.time(time);
+        }
+        if let Some(ratio) = self.ratio {
+            args = args.ratio(ratio);
+        }
+        if let Some(format) = self.format {
+            args = args.format(format);
+        }
+        if let Some(size) = self.size {
+            args = args.size(size);
+        }
+
+        if let Some(domain) = &self.domain {
+            args = args.domain(domain);
+        }
+        if let Some(language) = &self.language {
+            args = args.language(language);
+        }
+        if let Some(region) = &self.region {
+            args = args.region(region);
+        }
+        if let Some(country) = &self.country {
+            args = args.country(country);
+        }
+        if let Some(safe_search) = self.safe_search {
+            args = args.safe_search(safe_search);
+        }
+
+        for (key, value) in &self.raw_params {
+            args = args.raw_param(key, value);
+        }
+
+        Ok(args)
+    }
+}
+
+/// The `politeness` table. Anything left out keeps the value it had before.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolitenessTable {
+    rate_limit: Option<f64>,
+    burst: Option<u32>,
+    crawl_delay: Option<Seconds>,
+}
+
+impl PolitenessTable {
+    fn apply(&self, mut politeness: Politeness) -> Result<Politeness, (&'static str, String)> {
+        if let Some(rate) = self.rate_limit {
+            if !(rate.is_finite() && rate >= 0.0) {
+                return Err((
+                    ".politeness.rate_limit",
+                    format!(
+                        "{} is not a number of requests a second, or 0 for no limit",
+                        rate
+                    ),
+                ));
+            }
+            politeness = politeness.rate_limit(rate);
+        }
+        if let Some(burst) = self.burst {
+            if burst == 0 {
+                return Err((".politeness.burst", String::from("must be at least 1")));
+            }
+            politeness = politeness.burst(burst);
+        }
+        if let Some(Seconds(delay)) = self.crawl_delay {
+            politeness = politeness.crawl_delay(delay);
+        }
+
+        Ok(politeness)
+    }
+}
+
+/// The `retry` table. Anything left out keeps the value it had before.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetryTable {
+    attempts: Option<u32>,
+    backoff: Option<Seconds>,
+    max_backoff: Option<Seconds>,
+    jitter: Option<bool>,
+    retry_on: Option<Vec<FailureKind>>,
+}
+
+impl RetryTable {
+    fn apply(&self, mut retry: RetryPolicy) -> Result<RetryPolicy, (&'static str, String)> {
+        if let Some(attempts) = self.attempts {
+            if attempts == 0 {
+                return Err((".retry.attempts", String::from("must be at least 1")));
+            }
+            retry = retry.attempts(attempts);
+        }
+
+        let (backoff, max_backoff) = retry.get_backoff();
+        let backoff = self.backoff.map_or(backoff, |Seconds(backoff)| backoff);
+        let max_backoff = self.max_backoff.map_or(max_backoff, |Seconds(max)| max);
+        if max_backoff < backoff {
+            return Err((
+                ".retry.max_backoff",
+                format!(
+                    "must be at least the backoff of {} seconds",
+                    backoff.as_secs_f64()
+                ),
+            ));
+        }
+        retry = retry.backoff(backoff, max_backoff);
+
+        if let Some(jitter) = self.jitter {
+            retry = retry.jitter(jitter);
+        }
+        if let Some(kinds) = &self.retry_on {
+            retry = retry.retry_on(kinds.iter().copied());
+        }
+
+        Ok(retry)
+    }
+}
+
+/// A timeout or wait, written in seconds.
+#[derive(Clone, Copy)]
+struct Seconds(Duration);
+
+impl<'de> Deserialize<'de> for Seconds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Duration::try_from_secs_f64(f64::deserialize(deserializer)?)
+            .map(Seconds)
+            .map_err(de::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+struct Backends(
+    #[serde(deserialize_with = "serialize::backends::deserialize")] Vec<Arc<dyn SearchBackend>>,
+);
+
+/// An error encountered while loading a job.
+#[derive(Debug)]
+pub enum JobError {
+    /// The job file couldn't be read.
+    Io(io::Error),
+    /// The job file isn't a `.toml` or `.json` file.
+    Extension(PathBuf),
+    /// The job is invalid.
+    /// The line and column are those of the value that caused the error, when it can be found in the file,
+    /// and the key is its path, such as `queries[2].color`.
+    Invalid {
+        line: Option<usize>,
+        column: Option<usize>,
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Unable to read the job file: {}", err),
+            Self::Extension(path) => write!(
+                f,
+                "{} is not a job file, which should end in .toml or .json",
+                path.display()
+            ),
+            Self::Invalid {
+                line,
+                column,
+                key,
+                message,
+            } => {
+                match (line, column) {
+                    (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+                    (Some(line), None) => write!(f, "line {}: ", line)?,
+                    _ => (),
+                }
+                if !key.is_empty() {
+                    write!(f, "{}: ", key)?;
+                }
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+impl From<io::Error> for JobError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
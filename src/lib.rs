@@ -1,16 +1,16 @@
 //! A crate designed to search Google Images based on provided arguments.
-//! Due to the limitations of using only a single request to fetch images, only a max of about 100 images can be found per request.
+//! Google only returns about 100 images per page of results, so follow-up pages are requested automatically until the limit is reached or Google runs out of results.
 //! These images may be protected under copyright, and you shouldn't do anything punishable with them, like using them for commercial use.
 //!
 //! # Examples
 //! Using the asynchronous API requires some sort of async runtime, usually [`tokio`](https://crates.io/crates/tokio), which can be added to your `Cargo.toml` like so:
-//! ```
+//! ```toml
 //! [dependencies]
 //! image_search = "0.4"
 //! tokio = { version = "1", features = ["full"] }
 //! ```
 //! It can be used like this:
-//! ```
+//! ```no_run
 //! extern crate tokio;
 //! extern crate image_search;
 //!
@@ -21,14 +21,14 @@
 //! async fn main() -> Result<(), image_search::Error> {
 //!     let args = Arguments::new("example", 10)
 //!         .color(image_search::Color::Gray)
-//!         .directory(PathBuf::new("downloads")); // Only affects the download function
+//!         .directory(PathBuf::from("downloads")); // Only affects the download function
 //!     
 //!     // Returns the urls of the search results
 //!     let _image_urls = urls(args.clone()).await?;
 //!     // Returns the search results as Image structs
 //!     let _images = search(args.clone()).await?;
-//!     // Downloads the search results and returns the paths to the files
-//!     let _paths = download(args).await?;
+//!     // Downloads the search results and reports where each one was saved, or why it wasn't
+//!     let _report = download(args).await?;
 //!
 //!     Ok(())
 //! }
@@ -36,12 +36,12 @@
 //!
 //! # Blocking
 //! There is an optional "blocking" API that can be enabled:
-//! ```
+//! ```toml
 //! [dependencies]
 //! image_search = { version = "0.4", features = ["blocking"] }
 //! ```
 //! This is called like so:
-//! ```
+//! ```ignore
 //! extern crate image_search;
 //!
 //! use std::path::PathBuf;
@@ -54,41 +54,73 @@
 //!     
 //!     let _image_urls = urls(args.clone())?;
 //!     let _images = search(args.clone())?;
-//!     let _paths = download(args)?;
+//!     let _report = download(args)?;
 //!
 //!     Ok(())
 //! }
 //! ```
 
+pub mod backend;
+mod batch;
 #[cfg(feature = "blocking")]
 pub mod blocking;
-
+#[cfg(feature = "jobs")]
+pub mod jobs;
+mod politeness;
+mod query;
+mod report;
+mod retry;
+mod robots;
+pub mod scheduler;
+#[cfg(feature = "serde")]
+mod serialize;
+mod url;
+
+pub use backend::{Bing, Google, Parsed, SearchBackend, Strategy};
+pub use batch::{download_batch, Batch, QueryReport};
+pub use politeness::Politeness;
+pub use query::Query;
+pub use report::{DownloadReport, ImageReport, PageReport};
+pub use retry::{FailureKind, RetryPolicy};
+
+extern crate async_lock;
 extern crate async_std;
+extern crate crossbeam_queue;
 extern crate futures;
 extern crate glob;
 extern crate infer;
 extern crate serde_json;
 extern crate surf;
 
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fmt;
-use std::time::Duration;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use futures::future;
+use futures::stream::{self, Stream, StreamExt};
 use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
 
+use async_lock::OnceCell;
+use async_lock::{Semaphore, SemaphoreGuard, SemaphoreGuardArc};
+use politeness::HostBucket;
+use report::OnPage;
+use robots::Robots;
+use scheduler::Scheduler;
+
 /// Used to construct the arguments for searching and downloading images.
 ///
 /// # Example
-/// ```
+/// ```no_run
 /// extern crate tokio;
 /// extern crate image_search;
 ///
-/// use image_search::{self, Arguments};
+/// use image_search::Arguments;
 ///
 /// #[tokio::main]
 /// async fn main() -> Result<(), image_search::Error> {
@@ -96,14 +128,35 @@ use std::sync::{Arc, Mutex};
 ///         .color(image_search::Color::Black)
 ///         .ratio(image_search::Ratio::Square);
 ///     let images = image_search::search(args).await?;
+///
+///     Ok(())
 /// }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default = "serialize::defaults")
+)]
 pub struct Arguments {
     query: String,
     limit: usize,
     thumbnails: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serialize::seconds"))]
     timeout: Option<Duration>,
     directory: Option<PathBuf>,
+    #[cfg_attr(feature = "serde", serde(with = "serialize::backends"))]
+    backends: Vec<Arc<dyn SearchBackend>>,
+    snapshot: Option<PathBuf>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    on_page: Option<OnPage>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "serialize::at_least_one"))]
+    concurrency: usize,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "serialize::at_least_one"))]
+    host_concurrency: usize,
+    politeness: Politeness,
+    retry: RetryPolicy,
+    robots: Option<String>,
 
     color: Color,
     color_type: ColorType,
@@ -112,40 +165,123 @@ pub struct Arguments {
     time: Time,
     ratio: Ratio,
     format: Format,
+    size: Size,
+
+    domain: Option<String>,
+    language: Option<String>,
+    region: Option<String>,
+    country: Option<String>,
+    safe_search: SafeSearch,
+
+    raw_params: Vec<(String, String)>,
 }
 
 impl Arguments {
-    fn params(&self) -> String {
-        let split = &String::from("%2C");
-        let mut params_str = String::new();
-
-        let color = self.color.param();
-        let color_type = self.color_type.param();
-        let license = self.license.param();
-        let image_type = self.image_type.param();
-        let time = self.time.param();
-        let ratio = self.ratio.param();
-        let format = self.format.param();
-        let params = [color, color_type, license, image_type, time, ratio, format];
-
-        for param in params.iter() {
-            if param.len() > 1 {
-                params_str += split;
-                params_str += param;
+    /// The `tbs` values for the filters that are set, in the order Google lists them.
+    /// `ic:specific` is only needed to pick a specific color, and narrows down the results of every other filter if it's left in.
+    fn filters(&self) -> Vec<String> {
+        let mut filters = Vec::new();
+        if !matches!(self.color, Color::None) {
+            filters.push(String::from("ic:specific"));
+        }
+
+        let params = [
+            self.color.param(),
+            self.color_type.param(),
+            self.license.param(),
+            self.image_type.param(),
+            self.time.param(),
+            self.ratio.param(),
+            self.format.param(),
+            self.size.param(),
+        ];
+        filters.extend(params.into_iter().filter(|param| !param.is_empty()));
+        filters
+    }
+
+    /// The `tbs` values added with [`raw_param`](Arguments::raw_param).
+    fn raw_filters(&self) -> impl Iterator<Item = &str> {
+        self.raw_params
+            .iter()
+            .filter(|(key, _)| key == "tbs")
+            .flat_map(|(_, value)| value.split(','))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// The filters as a comma separated list of `tbs` values, including the raw ones.
+    fn tbs(&self) -> String {
+        let mut tbs = self.filters();
+        tbs.extend(self.raw_filters().map(str::to_owned));
+        tbs.join(",")
+    }
+
+    /// Checks that the arguments can be turned into a valid search.
+    fn validate(&self) -> SearchResult<()> {
+        self.time.validate()?;
+
+        if let Some(domain) = &self.domain {
+            if !backend::is_google_domain(domain) {
+                return Err(Error::Arguments(ArgumentError::invalid("domain", domain)));
+            }
+        }
+
+        if let Size::Exact { width, height } = self.size {
+            if width == 0 || height == 0 {
+                return Err(Error::Arguments(ArgumentError::invalid(
+                    "size",
+                    &self.size.to_string(),
+                )));
+            }
+        }
+
+        if !matches!(self.color, Color::None) && !matches!(self.color_type, ColorType::None) {
+            return Err(Error::Arguments(ArgumentError::Conflict {
+                first: format!("color({})", self.color),
+                second: format!("color_type({})", self.color_type),
+            }));
+        }
+
+        // A raw value can't be told apart from a typed one once it's in the list,
+        // so setting the same filter both ways would leave it up to Google which one is used.
+        let filters = self.filters();
+        for raw in self.raw_filters() {
+            let key = raw.split(':').next().unwrap_or(raw);
+            let typed = filters
+                .iter()
+                .flat_map(|filter| filter.split(','))
+                .find(|filter| filter.split(':').next() == Some(key));
+
+            if let Some(typed) = typed {
+                return Err(Error::Arguments(ArgumentError::Conflict {
+                    first: format!("raw_param(tbs, {})", raw),
+                    second: String::from(typed),
+                }));
             }
         }
 
-        params_str
+        Ok(())
     }
 
-    pub fn new(query: &str, limit: usize) -> Arguments {
+    /// Creates the arguments for searching `query`, which can be plain text or a [`Query`],
+    /// for at most `limit` images as set by [`limit`](Self::limit).
+    pub fn new<Q: Into<String>>(query: Q, limit: usize) -> Arguments {
         Arguments {
-            query: query.to_owned(),
+            query: query.into(),
             limit,
             thumbnails: false,
             timeout: Some(Duration::from_secs(20)),
 
             directory: None,
+            backends: vec![Arc::new(Google)],
+            snapshot: None,
+            on_page: None,
+            concurrency: 16,
+            host_concurrency: 4,
+            politeness: Politeness::new(),
+            retry: RetryPolicy::new(),
+            robots: None,
+
             color: Color::None,
             color_type: ColorType::None,
             license: License::None,
@@ -153,9 +289,25 @@ impl Arguments {
             time: Time::None,
             ratio: Ratio::None,
             format: Format::None,
+            size: Size::None,
+
+            domain: None,
+            language: None,
+            region: None,
+            country: None,
+            safe_search: SafeSearch::None,
+
+            raw_params: Vec::new(),
         }
     }
 
+    /// Sets the maximum amount of images to fetch.
+    /// A limit of 0 fetches every page of results Google has, up to 20 of them.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
     /// Sets the directory the images will be downloaded to. Only used in the download function.
     pub fn directory<P: Into<PathBuf>>(mut self, dir: P) -> Self {
         self.directory = Some(dir.into());
@@ -177,6 +329,99 @@ impl Arguments {
         self
     }
 
+    /// Sets the search engine the images are fetched from. Defaults to [`Google`].
+    ///
+    /// Filters that the engine doesn't support are ignored.
+    pub fn backend<B: SearchBackend + 'static>(mut self, backend: B) -> Self {
+        self.backends = vec![Arc::new(backend)];
+        self
+    }
+
+    /// Adds another search engine to fetch images from, alongside the ones already selected.
+    ///
+    /// When more than one engine is selected, they are all searched concurrently and their results are interleaved by rank,
+    /// with duplicates removed by url and thumbnail.
+    /// This makes it easier to reach the limit when a single engine doesn't return enough images.
+    pub fn add_backend<B: SearchBackend + 'static>(mut self, backend: B) -> Self {
+        self.backends.push(Arc::new(backend));
+        self
+    }
+
+    /// Saves the body of a response to `path` whenever the images can't be parsed from it.
+    /// The path is reported in [`ParseError::snapshot`], so the failing page can be looked into or attached to a bug report.
+    pub fn snapshot<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    /// Calls `callback` with a [`PageReport`] for every page of results that is parsed, by [`search`], [`search_stream`] and [`download`] alike.
+    /// This shows when the images were only found by a fallback [`Strategy`], or when some of the results couldn't be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// extern crate tokio;
+    /// extern crate image_search;
+    ///
+    /// use image_search::{Arguments, Strategy};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), image_search::Error> {
+    ///     let args = Arguments::new("cats", 10).on_page(|page| {
+    ///         if page.strategy != Strategy::Script || page.skipped > 0 {
+    ///             eprintln!("{} at {}: {:?}, skipped {}", page.backend, page.offset, page.strategy, page.skipped);
+    ///         }
+    ///     });
+    ///     let images = image_search::search(args).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn on_page<F: Fn(&PageReport) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.on_page = Some(OnPage(Arc::new(callback)));
+        self
+    }
+
+    /// Sets the most images that are downloaded at once. Defaults to 16.
+    /// Only used in the download function.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the most images that are downloaded at once from the same host. Defaults to 4.
+    /// See [`Politeness`] for why each host is limited separately.
+    /// Only used in the download function.
+    pub fn host_concurrency(mut self, host_concurrency: usize) -> Self {
+        self.host_concurrency = host_concurrency.max(1);
+        self
+    }
+
+    /// Sets the rate limit and crawl delay for each image host. Defaults to [`Politeness::new`].
+    /// Only used in the download function.
+    pub fn politeness(mut self, politeness: Politeness) -> Self {
+        self.politeness = politeness;
+        self
+    }
+
+    /// Sets how failed requests are retried, for both the search and each image. Defaults to [`RetryPolicy::new`].
+    /// An image that still fails after every attempt is given up on, and the next one is downloaded in its place.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Only downloads images that robots.txt allows `user_agent` to fetch. Off by default.
+    ///
+    /// Each host's robots.txt is fetched the first time an image is downloaded from it, and kept for the rest of the download.
+    /// A host without one allows everything, while nothing is downloaded from a host whose robots.txt can't be fetched,
+    /// and the error fetching it is reported for each of its images.
+    /// The user agent is also sent with every image request, so hosts can tell who is asking.
+    /// Only used in the download function.
+    pub fn robots<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.robots = Some(user_agent.into());
+        self
+    }
+
     /// Sets the color that Google will filter by.
     pub fn color(mut self, color: Color) -> Self {
         self.color = color;
@@ -201,7 +446,7 @@ impl Arguments {
         self
     }
 
-    /// Sets how long ago the images can be posted.
+    /// Sets how long ago the images can be posted, or the range of dates they were posted in.
     pub fn time(mut self, time: Time) -> Self {
         self.time = time;
         self
@@ -218,6 +463,171 @@ impl Arguments {
         self.format = format;
         self
     }
+
+    /// Sets the image size that Google will filter by.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the country domain of Google to search, such as `"co.uk"` or `"de"`. Defaults to `"com"`.
+    /// Pinning this keeps results from changing with the location the requests are sent from.
+    /// Searching fails with [`ArgumentError::Invalid`] if it isn't `com`, a two letter country code, or `co.` or `com.` followed by one.
+    pub fn domain<S: Into<String>>(mut self, domain: S) -> Self {
+        self.domain = Some(domain.into().trim_matches('.').to_lowercase());
+        self
+    }
+
+    /// Sets the language of the results page, as a language code such as `"en"` or `"pt-BR"`.
+    pub fn language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the region the search appears to come from, as a two letter country code such as `"us"`.
+    /// Results from that region are preferred, but not required.
+    pub fn region<S: Into<String>>(mut self, region: S) -> Self {
+        self.region = Some(region.into().to_lowercase());
+        self
+    }
+
+    /// Only finds images from sites in a country, as a two letter country code such as `"US"`.
+    pub fn country<S: Into<String>>(mut self, country: S) -> Self {
+        self.country = Some(country.into().to_uppercase());
+        self
+    }
+
+    /// Sets how explicit content is filtered.
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.safe_search = safe_search;
+        self
+    }
+
+    /// Adds a parameter to the search url as is, for filters this crate doesn't support yet.
+    ///
+    /// A `tbs` value is added to the list of filters Google is sent, and is checked against the filters that are already set,
+    /// so searching fails with [`ArgumentError::Conflict`] instead of sending Google the same filter twice.
+    /// Any other parameter is added to the url of every backend.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate image_search;
+    ///
+    /// use image_search::{Arguments, Google, SearchBackend, Time};
+    ///
+    /// let args = Arguments::new("cats", 10)
+    ///     .time(Time::Week)
+    ///     .raw_param("tbs", "itp:photo")
+    ///     .raw_param("tbm", "isch");
+    /// assert_eq!(
+    ///     Google.build_url(&args, 0),
+    ///     "https://www.google.com/search?udm=2&q=cats&tbs=qdr%3Aw%2Citp%3Aphoto&tbm=isch"
+    /// );
+    /// ```
+    pub fn raw_param<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.raw_params.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// Read access to the arguments, for use by [`SearchBackend`] implementations.
+impl Arguments {
+    pub fn get_query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn get_backends(&self) -> Vec<&dyn SearchBackend> {
+        self.backends
+            .iter()
+            .map(|backend| backend.as_ref())
+            .collect()
+    }
+
+    pub fn get_concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn get_host_concurrency(&self) -> usize {
+        self.host_concurrency
+    }
+
+    pub fn get_politeness(&self) -> &Politeness {
+        &self.politeness
+    }
+
+    pub fn get_retry(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// The user agent robots.txt is followed for, or `None` if it isn't.
+    pub fn get_robots(&self) -> Option<&str> {
+        self.robots.as_deref()
+    }
+
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+
+    pub fn get_color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    pub fn get_license(&self) -> License {
+        self.license
+    }
+
+    pub fn get_image_type(&self) -> ImageType {
+        self.image_type
+    }
+
+    pub fn get_time(&self) -> Time {
+        self.time
+    }
+
+    pub fn get_ratio(&self) -> Ratio {
+        self.ratio
+    }
+
+    pub fn get_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn get_size(&self) -> Size {
+        self.size
+    }
+
+    pub fn get_domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn get_language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn get_region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn get_country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    pub fn get_safe_search(&self) -> SafeSearch {
+        self.safe_search
+    }
+
+    /// The parameters added with [`raw_param`](Arguments::raw_param), in the order they were added.
+    pub fn get_raw_params(&self) -> &[(String, String)] {
+        &self.raw_params
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -238,6 +648,22 @@ pub enum Color {
 }
 
 impl Color {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 12] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Teal,
+        Self::Blue,
+        Self::Purple,
+        Self::Pink,
+        Self::White,
+        Self::Gray,
+        Self::Black,
+        Self::Brown,
+    ];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -266,6 +692,9 @@ pub enum ColorType {
 }
 
 impl ColorType {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 3] = [Self::Color, Self::Grayscale, Self::Transparent];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -284,6 +713,9 @@ pub enum License {
 }
 
 impl License {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 2] = [Self::CreativeCommons, Self::Other];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -304,6 +736,15 @@ pub enum ImageType {
 }
 
 impl ImageType {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 5] = [
+        Self::Face,
+        Self::Photo,
+        Self::Clipart,
+        Self::Lineart,
+        Self::Animated,
+    ];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -323,17 +764,162 @@ pub enum Time {
     Week,
     Month,
     Year,
+    /// Only finds images posted between two dates, including both of them.
+    /// Use [`Time::range`] to make sure `from` isn't after `to`.
+    Range {
+        from: Date,
+        to: Date,
+    },
 }
 
 impl Time {
+    /// Creates a [`Time::Range`], checking that `from` isn't after `to`.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate image_search;
+    ///
+    /// use image_search::{Date, Time};
+    ///
+    /// let from = Date::new(2024, 7, 26).unwrap();
+    /// let to = Date::new(2024, 8, 11).unwrap();
+    ///
+    /// assert!(Time::range(from, to).is_ok());
+    /// assert!(Time::range(to, from).is_err());
+    /// ```
+    pub fn range(from: Date, to: Date) -> SearchResult<Time> {
+        let time = Time::Range { from, to };
+        time.validate()?;
+        Ok(time)
+    }
+
+    fn validate(&self) -> SearchResult<()> {
+        match *self {
+            Self::Range { from, to } if from > to => {
+                Err(Error::Arguments(ArgumentError::DateRange { from, to }))
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn param(&self) -> String {
-        String::from(match self {
-            Self::None => "",
-            Self::Day => "qdr:d",
-            Self::Week => "qdr:w",
-            Self::Month => "qdr:m",
-            Self::Year => "qdr:y",
-        })
+        match self {
+            Self::None => String::new(),
+            Self::Day => String::from("qdr:d"),
+            Self::Week => String::from("qdr:w"),
+            Self::Month => String::from("qdr:m"),
+            Self::Year => String::from("qdr:y"),
+            Self::Range { from, to } => format!(
+                "cdr:1,cd_min:{}/{}/{},cd_max:{}/{}/{}",
+                from.month, from.day, from.year, to.month, to.day, to.year
+            ),
+        }
+    }
+}
+
+/// Ranges are written as `from..to`, for example `2024-07-26..2024-08-11`.
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Day => f.write_str("day"),
+            Self::Week => f.write_str("week"),
+            Self::Month => f.write_str("month"),
+            Self::Year => f.write_str("year"),
+            Self::Range { from, to } => write!(f, "{}..{}", from, to),
+        }
+    }
+}
+
+impl FromStr for Time {
+    type Err = ArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((from, to)) = s.split_once("..") {
+            let (from, to) = (from.parse()?, to.parse()?);
+            if from > to {
+                return Err(ArgumentError::DateRange { from, to });
+            }
+            return Ok(Self::Range { from, to });
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            _ => Err(ArgumentError::invalid("time", s)),
+        }
+    }
+}
+
+/// A calendar date, used for [`Time::Range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Creates a date, returning `None` if the month or day don't exist.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => return None,
+        };
+
+        if day == 0 || day > days {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Dates are read in the `YYYY-MM-DD` format they are displayed in.
+impl FromStr for Date {
+    type Err = ArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('-');
+        let date = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(year), Some(month), Some(day), None) => {
+                match (year.parse(), month.parse(), day.parse()) {
+                    (Ok(year), Ok(month), Ok(day)) => Date::new(year, month, day),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        date.ok_or_else(|| ArgumentError::invalid("date", s))
     }
 }
 
@@ -347,6 +933,9 @@ pub enum Ratio {
 }
 
 impl Ratio {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 4] = [Self::Tall, Self::Square, Self::Wide, Self::Panoramic];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -372,6 +961,18 @@ pub enum Format {
 }
 
 impl Format {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 8] = [
+        Self::Jpg,
+        Self::Gif,
+        Self::Png,
+        Self::Bmp,
+        Self::Svg,
+        Self::Webp,
+        Self::Ico,
+        Self::Raw,
+    ];
+
     fn param(&self) -> String {
         String::from(match self {
             Self::None => "",
@@ -387,10 +988,209 @@ impl Format {
     }
 }
 
-/// Contains info about an image including the original url, the dimensions of the image (x, y), the url of the thumbnail, and the name of the source.
+/// Filters images by their size.
 ///
 /// # Example
 /// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, Google, MinSize, SearchBackend, Size};
+///
+/// let args = Arguments::new("mountains", 10).size(Size::LargerThan(MinSize::Mp4));
+/// assert!(Google.build_url(&args, 0).ends_with("&tbs=isz%3Alt%2Cislt%3A4mp"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum Size {
+    None,
+    Large,
+    Medium,
+    Icon,
+    /// Only finds images at least as large as the given size.
+    LargerThan(MinSize),
+    /// Only finds images with exactly the given dimensions.
+    Exact {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl Size {
+    fn param(&self) -> String {
+        match self {
+            Self::None => String::new(),
+            Self::Large => String::from("isz:l"),
+            Self::Medium => String::from("isz:m"),
+            Self::Icon => String::from("isz:i"),
+            Self::LargerThan(min) => format!("isz:lt,islt:{}", min.param()),
+            Self::Exact { width, height } => format!("isz:ex,iszw:{},iszh:{}", width, height),
+        }
+    }
+}
+
+/// Thresholds are written as `larger_than_` followed by the [`MinSize`], like `larger_than_4mp`,
+/// and exact sizes as `WIDTHxHEIGHT`, like `1920x1080`.
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Large => f.write_str("large"),
+            Self::Medium => f.write_str("medium"),
+            Self::Icon => f.write_str("icon"),
+            Self::LargerThan(min) => write!(f, "larger_than_{}", min),
+            Self::Exact { width, height } => write!(f, "{}x{}", width, height),
+        }
+    }
+}
+
+impl FromStr for Size {
+    type Err = ArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let size = s.trim().to_lowercase();
+        if let Some(min) = size.strip_prefix("larger_than_") {
+            return Ok(Self::LargerThan(min.parse()?));
+        }
+        if let Some((width, height)) = size.split_once('x') {
+            if let (Ok(width), Ok(height)) = (width.parse::<u32>(), height.parse::<u32>()) {
+                if width == 0 || height == 0 {
+                    return Err(ArgumentError::invalid("size", s));
+                }
+                return Ok(Self::Exact { width, height });
+            }
+        }
+
+        match size.as_str() {
+            "none" => Ok(Self::None),
+            "large" => Ok(Self::Large),
+            "medium" => Ok(Self::Medium),
+            "icon" => Ok(Self::Icon),
+            _ => Err(ArgumentError::invalid("size", s)),
+        }
+    }
+}
+
+/// The thresholds available for [`Size::LargerThan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MinSize {
+    /// 400x300
+    Qsvga,
+    /// 640x480
+    Vga,
+    /// 800x600
+    Svga,
+    /// 1024x768
+    Xga,
+    /// 2 megapixels
+    Mp2,
+    /// 4 megapixels
+    Mp4,
+    /// 6 megapixels
+    Mp6,
+    /// 8 megapixels
+    Mp8,
+    /// 10 megapixels
+    Mp10,
+    /// 12 megapixels
+    Mp12,
+    /// 15 megapixels
+    Mp15,
+    /// 20 megapixels
+    Mp20,
+    /// 40 megapixels
+    Mp40,
+    /// 70 megapixels
+    Mp70,
+}
+
+impl MinSize {
+    /// Every variant, for looking one up by its parameter.
+    const ALL: [Self; 14] = [
+        Self::Qsvga,
+        Self::Vga,
+        Self::Svga,
+        Self::Xga,
+        Self::Mp2,
+        Self::Mp4,
+        Self::Mp6,
+        Self::Mp8,
+        Self::Mp10,
+        Self::Mp12,
+        Self::Mp15,
+        Self::Mp20,
+        Self::Mp40,
+        Self::Mp70,
+    ];
+
+    fn param(&self) -> &'static str {
+        match self {
+            Self::Qsvga => "qsvga",
+            Self::Vga => "vga",
+            Self::Svga => "svga",
+            Self::Xga => "xga",
+            Self::Mp2 => "2mp",
+            Self::Mp4 => "4mp",
+            Self::Mp6 => "6mp",
+            Self::Mp8 => "8mp",
+            Self::Mp10 => "10mp",
+            Self::Mp12 => "12mp",
+            Self::Mp15 => "15mp",
+            Self::Mp20 => "20mp",
+            Self::Mp40 => "40mp",
+            Self::Mp70 => "70mp",
+        }
+    }
+
+    /// The smallest dimensions (width, height) matching the threshold, using a 4:3 aspect ratio for the megapixel sizes.
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Qsvga => (400, 300),
+            Self::Vga => (640, 480),
+            Self::Svga => (800, 600),
+            Self::Xga => (1024, 768),
+            Self::Mp2 => (1600, 1200),
+            Self::Mp4 => (2272, 1704),
+            Self::Mp6 => (2816, 2112),
+            Self::Mp8 => (3264, 2448),
+            Self::Mp10 => (3648, 2736),
+            Self::Mp12 => (4000, 3000),
+            Self::Mp15 => (4480, 3360),
+            Self::Mp20 => (5120, 3840),
+            Self::Mp40 => (7296, 5472),
+            Self::Mp70 => (9600, 7200),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SafeSearch {
+    /// Leaves the setting up to the search engine, which usually blurs explicit images.
+    None,
+    /// Doesn't filter explicit images.
+    Off,
+    /// Blurs explicit images.
+    Blur,
+    /// Leaves out explicit images entirely.
+    Strict,
+}
+
+impl SafeSearch {
+    /// Every variant other than `None`, for looking one up by its parameter.
+    const ALL: [Self; 3] = [Self::Off, Self::Blur, Self::Strict];
+
+    fn param(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Off => "off",
+            Self::Blur => "images",
+            Self::Strict => "active",
+        }
+    }
+}
+
+/// Contains info about an image including the original url, the dimensions of the image (x, y), the url of the thumbnail, and the name of the source.
+///
+/// # Example
+/// ```text
 /// Image {
 ///     url: "https://www.example.com/static/image.jpg",
 ///     width: 1920,
@@ -398,7 +1198,9 @@ impl Format {
 ///     thumbnail: "https://encrypted-tbn0.gstatic.com/images?q=tbn:ANd9GcQQazt0j8bbA34OYbfE9hf7g_bzFGwbSZmwflwVw-rnOSOmdSX03xYzHZkm_TsmbnM3m88&usqp=CAU",
 ///     source: "https://www.example.com/articles/example.html"
 /// }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Image {
     pub url: String,
     pub width: i64,
@@ -409,17 +1211,47 @@ pub struct Image {
 
 #[derive(Debug)]
 pub enum Error {
-    Parse,
+    Parse(ParseError),
     Dir(io::Error),
     Network(surf::Error),
+    /// The search engine asked for cookie consent instead of returning results, which happens to requests from the EU.
+    Consent,
+    /// The search engine flagged the requests as unusual traffic and asked for a CAPTCHA to be solved.
+    Captcha,
+    /// The search engine answered with `429 Too Many Requests`.
+    /// `retry_after` is how long it asked to wait before trying again, if it said.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    /// The arguments can't be turned into a valid search.
+    Arguments(ArgumentError),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Parse        => write!(f, "Unable to parse images from json. Google may have changed the way their data is stored"),
-            Self::Dir(err)     => write!(f, "Unable to find or create: {}", err),
+            Self::Parse(err) => write!(f, "{}", err),
+            Self::Dir(err) => write!(f, "Unable to find or create: {}", err),
             Self::Network(err) => write!(f, "GET request failed: {}", err),
+            Self::Consent => write!(
+                f,
+                "The search engine asked for cookie consent instead of returning results"
+            ),
+            Self::Captcha => write!(
+                f,
+                "The search engine detected unusual traffic and asked for a CAPTCHA to be solved"
+            ),
+            Self::RateLimited {
+                retry_after: Some(wait),
+            } => write!(
+                f,
+                "Rate limited by the search engine, retry after {} seconds",
+                wait.as_secs()
+            ),
+            Self::RateLimited { retry_after: None } => {
+                write!(f, "Rate limited by the search engine")
+            }
+            Self::Arguments(err) => write!(f, "Invalid arguments: {}", err),
         }
     }
 }
@@ -427,17 +1259,131 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn description(&self) -> &str {
         match *self {
-            Self::Parse => "Unable to parse images from json",
+            Self::Parse(_) => "Unable to parse images from the search results",
             Self::Dir(_) => "Error when finding or creating directory",
             Self::Network(_) => "Failed to make GET request",
+            Self::Consent => "Search engine asked for cookie consent",
+            Self::Captcha => "Search engine asked for a CAPTCHA",
+            Self::RateLimited { .. } => "Rate limited by the search engine",
+            Self::Arguments(_) => "Invalid arguments",
         }
     }
 }
 
-impl From<io::Error> for Error {
-    fn from(value: io::Error) -> Self {
-        Self::Dir(value)
-    }
+/// Details on why images couldn't be parsed from a page of results.
+#[derive(Debug)]
+pub struct ParseError {
+    /// How far the parser got before failing.
+    pub stage: ParseStage,
+    /// The HTTP status of the response, if it came from a request.
+    pub status: Option<u16>,
+    /// Where the body of the response was saved, if [`Arguments::snapshot`] was set.
+    pub snapshot: Option<PathBuf>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unable to parse images from the search results: {}",
+            self.stage
+        )?;
+        if let Some(status) = self.status {
+            write!(f, " (HTTP {})", status)?;
+        }
+        write!(
+            f,
+            ". The search engine may have changed the way their data is stored"
+        )?;
+        if let Some(snapshot) = &self.snapshot {
+            write!(f, ". The response was saved to {}", snapshot.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The point at which parsing a page of results failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseStage {
+    /// The data holding the results wasn't found in the page.
+    BlobNotFound,
+    /// The start of the data was found, but not where it ends.
+    ScriptEndNotFound,
+    /// The data was found, but isn't valid json.
+    InvalidJson,
+    /// The data was read, but no images could be found in it.
+    NoEntries,
+}
+
+impl fmt::Display for ParseStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlobNotFound => write!(f, "the data holding the results was not found"),
+            Self::ScriptEndNotFound => {
+                write!(f, "the end of the data holding the results was not found")
+            }
+            Self::InvalidJson => write!(f, "the data holding the results is not valid json"),
+            Self::NoEntries => write!(f, "no images were found in the results"),
+        }
+    }
+}
+
+/// A problem with the [`Arguments`] found before any request is made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgumentError {
+    /// The start of a [`Time::Range`] is after its end.
+    DateRange { from: Date, to: Date },
+    /// Two arguments set the same filter, such as a [`Color`] along with a [`ColorType`].
+    /// Both are named the way they were set, for example `color(red)` and `color_type(grayscale)`.
+    Conflict { first: String, second: String },
+    /// The url given to [`Arguments::from_url`] isn't a Google search.
+    Url(String),
+    /// A value that was read from text, such as `"purple"` for a [`Color`], doesn't name anything that `argument` can be set to.
+    Invalid {
+        argument: &'static str,
+        value: String,
+    },
+}
+
+impl ArgumentError {
+    fn invalid(argument: &'static str, value: &str) -> ArgumentError {
+        ArgumentError::Invalid {
+            argument,
+            value: value.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DateRange { from, to } => write!(
+                f,
+                "the date range starts on {} but ends earlier, on {}",
+                from, to
+            ),
+            Self::Conflict { first, second } => write!(
+                f,
+                "{} and {} set the same filter, so only one of them can be used",
+                first, second
+            ),
+            Self::Url(url) => write!(f, "{} is not the url of a Google search", url),
+            Self::Invalid { argument, value } => {
+                write!(f, "\"{}\" is not a valid {}", value, argument)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Dir(value)
+    }
 }
 
 impl From<surf::Error> for Error {
@@ -446,23 +1392,70 @@ impl From<surf::Error> for Error {
     }
 }
 
+/// Why an image couldn't be downloaded, as listed in a [`DownloadReport`].
 #[derive(Debug)]
-enum DownloadError {
-    Overflow,
-    Extension,
+pub enum DownloadError {
+    /// The request took longer than the [`timeout`](Arguments::timeout).
     Timeout,
-    Fs(std::io::Error),
+    /// The server answered with a status that isn't a success.
+    /// `retry_after` is how long it asked to wait before trying again, if it said.
+    Status {
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    /// The response wasn't an image that could be recognized.
+    NotAnImage,
+    /// The image couldn't be written to disk.
+    Fs(io::Error),
+    /// The request couldn't be made or was dropped.
     Network(surf::Error),
+    /// The url of the image couldn't be read, so it was never requested.
+    InvalidUrl(String),
+    /// The image was never requested.
+    Skipped(SkipReason),
+}
+
+/// Why an image was never requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The host's robots.txt doesn't allow the user agent set with [`Arguments::robots`] to fetch it.
+    RobotsDisallowed,
+}
+
+impl DownloadError {
+    /// The kind of failure this is, as used by [`RetryPolicy`], or `None` if the image was never requested.
+    pub fn kind(&self) -> Option<FailureKind> {
+        self.failure().map(|(kind, _)| kind)
+    }
+
+    /// The kind of failure this is and how long the server asked to wait before retrying, or `None` if it can't be retried at all.
+    fn failure(&self) -> Option<(FailureKind, Option<Duration>)> {
+        match self {
+            Self::Timeout => Some((FailureKind::Timeout, None)),
+            Self::Status {
+                status,
+                retry_after,
+            } => Some((FailureKind::from_status(*status), *retry_after)),
+            Self::NotAnImage => Some((FailureKind::NotAnImage, None)),
+            Self::Fs(_) => Some((FailureKind::Fs, None)),
+            Self::Network(_) => Some((FailureKind::Network, None)),
+            Self::InvalidUrl(_) | Self::Skipped(_) => None,
+        }
+    }
 }
 
 impl fmt::Display for DownloadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Overflow => write!(f, "Ran out of possible images"),
-            Self::Extension => write!(f, "Unable to determine file extension"),
             Self::Timeout => write!(f, "GET request timed out"),
+            Self::Status { status, .. } => write!(f, "Image request returned status {}", status),
+            Self::NotAnImage => write!(f, "Unable to determine file extension"),
             Self::Fs(err) => write!(f, "Problem when creating or writing to file: {}", err),
             Self::Network(err) => write!(f, "Unable to fetch image: {}", err),
+            Self::InvalidUrl(url) => write!(f, "Invalid image url: {}", url),
+            Self::Skipped(SkipReason::RobotsDisallowed) => {
+                write!(f, "Skipped since robots.txt disallows it")
+            }
         }
     }
 }
@@ -470,11 +1463,13 @@ impl fmt::Display for DownloadError {
 impl std::error::Error for DownloadError {
     fn description(&self) -> &str {
         match *self {
-            Self::Overflow => "Ran out of possible images",
-            Self::Extension => "File type not known or not an image",
             Self::Timeout => "GET request timed out",
+            Self::Status { .. } => "Image request returned an unsuccessful status",
+            Self::NotAnImage => "File type not known or not an image",
             Self::Fs(_) => "Error occured creating or writing to file",
             Self::Network(_) => "Error when making GET request to fetch image",
+            Self::InvalidUrl(_) => "Image url could not be parsed",
+            Self::Skipped(_) => "Image was skipped without being requested",
         }
     }
 }
@@ -508,18 +1503,129 @@ macro_rules! debug_display {
         })*
     }
 }
-debug_display!(for Image, Arguments, Color, ColorType, License, ImageType, Time, Ratio, Format);
+debug_display!(for Image, Arguments);
 
-/// Search for images based on the provided arguments and return images up to the provided limit.
+/// Implements `Display` and `FromStr` for an enum, using a stable lowercase name for each variant.
+/// Names are read case-insensitively.
+macro_rules! named {
+    ($t:ty, $argument:literal, { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    $(Self::$variant => $name,)+
+                })
+            }
+        }
+
+        impl FromStr for $t {
+            type Err = ArgumentError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.trim().to_lowercase().as_str() {
+                    $($name => Ok(Self::$variant),)+
+                    _ => Err(ArgumentError::invalid($argument, s)),
+                }
+            }
+        }
+    };
+}
+
+named!(Color, "color", {
+    None => "none",
+    Red => "red",
+    Orange => "orange",
+    Yellow => "yellow",
+    Green => "green",
+    Teal => "teal",
+    Blue => "blue",
+    Purple => "purple",
+    Pink => "pink",
+    White => "white",
+    Gray => "gray",
+    Black => "black",
+    Brown => "brown",
+});
+named!(ColorType, "color type", {
+    None => "none",
+    Color => "color",
+    Grayscale => "grayscale",
+    Transparent => "transparent",
+});
+named!(License, "license", {
+    None => "none",
+    CreativeCommons => "creative_commons",
+    Other => "other",
+});
+named!(ImageType, "image type", {
+    None => "none",
+    Face => "face",
+    Photo => "photo",
+    Clipart => "clipart",
+    Lineart => "lineart",
+    Animated => "animated",
+});
+named!(Ratio, "ratio", {
+    None => "none",
+    Tall => "tall",
+    Square => "square",
+    Wide => "wide",
+    Panoramic => "panoramic",
+});
+named!(Format, "format", {
+    None => "none",
+    Jpg => "jpg",
+    Gif => "gif",
+    Png => "png",
+    Bmp => "bmp",
+    Svg => "svg",
+    Webp => "webp",
+    Ico => "ico",
+    Raw => "raw",
+});
+named!(MinSize, "minimum size", {
+    Qsvga => "qsvga",
+    Vga => "vga",
+    Svga => "svga",
+    Xga => "xga",
+    Mp2 => "2mp",
+    Mp4 => "4mp",
+    Mp6 => "6mp",
+    Mp8 => "8mp",
+    Mp10 => "10mp",
+    Mp12 => "12mp",
+    Mp15 => "15mp",
+    Mp20 => "20mp",
+    Mp40 => "40mp",
+    Mp70 => "70mp",
+});
+named!(FailureKind, "failure kind", {
+    Timeout => "timeout",
+    Network => "network",
+    ServerError => "server_error",
+    RateLimited => "rate_limited",
+    ClientError => "client_error",
+    NotAnImage => "not_an_image",
+    Fs => "fs",
+});
+named!(SafeSearch, "safe search setting", {
+    None => "none",
+    Off => "off",
+    Blur => "blur",
+    Strict => "strict",
+});
+
+/// Search for images based on the provided arguments and return images up to the provided limit,
+/// or every image Google has if the limit is 0.
 ///
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// extern crate tokio;
 /// extern crate image_search;
 ///
@@ -544,33 +1650,368 @@ pub async fn search(args: Arguments) -> SearchResult<Vec<Image>> {
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
-async fn _search(args: Arguments) -> SearchResult<Vec<Image>> {
-    let url = build_url(&args);
-    let body = get(url).await?;
-
-    let imgs = match unpack(body) {
-        Some(i) => i,
-        None => return Err(Error::Parse),
+pub(crate) async fn _search(args: Arguments) -> SearchResult<Vec<Image>> {
+    let wanted = if args.limit > 0 {
+        args.limit
+    } else {
+        usize::MAX
     };
+    let mut imgs = fetch(&surf::Client::new(), &args, wanted).await?;
 
-    if imgs.len() > args.limit && args.limit > 0 {
-        Ok(imgs[..args.limit].to_vec())
+    if args.limit > 0 {
+        imgs.truncate(args.limit);
+    }
+
+    Ok(imgs)
+}
+
+/// Search for images based on the provided arguments and yield them as each page of results is parsed.
+///
+/// Unlike [`search`], this doesn't wait for the whole search to finish, so the caller can stop early or start working on images right away.
+/// The stream ends once `limit` images have been yielded, or when Google runs out of results if the limit is 0.
+///
+/// When more than one backend is selected, images are yielded in the order the backends answer, with duplicates removed.
+///
+/// # Errors
+/// The stream will yield an error for a backend and stop searching it if, on any page:
+/// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
+/// * The images are not able to be parsed, unless the page is past the last result and has none
+///
+/// # Examples
+///
+/// ```no_run
+/// extern crate futures;
+/// extern crate tokio;
+/// extern crate image_search;
+///
+/// use futures::StreamExt;
+/// use image_search::{Arguments, search_stream};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), image_search::Error> {
+///     let args = Arguments::new("example", 500);
+///     let mut images = Box::pin(search_stream(args));
+///
+///     while let Some(image) = images.next().await {
+///         println!("{}", image?.url);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn search_stream(args: Arguments) -> impl Stream<Item = SearchResult<Image>> {
+    let limit = if args.limit > 0 {
+        args.limit
     } else {
-        Ok(imgs)
+        usize::MAX
+    };
+
+    let client = surf::Client::new();
+    let streams = args
+        .backends
+        .iter()
+        .map(|backend| Box::pin(pages(client.clone(), args.clone(), backend.clone())));
+
+    let mut seen = Seen::default();
+    stream::select_all(streams)
+        .flat_map(|page| match page {
+            Ok(images) => stream::iter(images.into_iter().map(Ok).collect::<Vec<_>>()),
+            Err(err) => stream::iter(vec![Err(err)]),
+        })
+        .filter(move |image| {
+            future::ready(match image {
+                Ok(image) => seen.insert(image),
+                Err(_) => true,
+            })
+        })
+        .take(limit)
+}
+
+/// The maximum number of result pages requested for a single search, in case Google keeps returning new results forever.
+const MAX_PAGES: usize = 20;
+
+/// Fetches result pages until at least `wanted` unique images have been found or Google runs out of results.
+/// A `wanted` of 0 only fetches the first page.
+async fn fetch(client: &surf::Client, args: &Arguments, wanted: usize) -> SearchResult<Vec<Image>> {
+    if let [backend] = args.backends.as_slice() {
+        return fetch_from(client, args, backend.clone(), wanted).await;
+    }
+
+    let results = future::join_all(
+        args.backends
+            .iter()
+            .map(|backend| fetch_from(client, args, backend.clone(), wanted)),
+    )
+    .await;
+
+    let mut lists = Vec::new();
+    let mut error = None;
+    for result in results {
+        match result {
+            Ok(images) => lists.push(images),
+            Err(err) => {
+                error.get_or_insert(err);
+            }
+        }
+    }
+
+    match error {
+        Some(err) if lists.is_empty() => Err(err),
+        _ => Ok(interleave(lists)),
+    }
+}
+
+/// Fetches result pages from a single backend.
+async fn fetch_from(
+    client: &surf::Client,
+    args: &Arguments,
+    backend: Arc<dyn SearchBackend>,
+    wanted: usize,
+) -> SearchResult<Vec<Image>> {
+    let mut pages = Box::pin(pages(client.clone(), args.clone(), backend));
+    let mut images: Vec<Image> = Vec::new();
+
+    while let Some(page) = pages.next().await {
+        images.extend(page?);
+
+        if wanted == 0 || images.len() >= wanted {
+            break;
+        }
+    }
+
+    Ok(images)
+}
+
+/// Merges the results of several backends by taking the first image of each, then the second of each, and so on.
+/// Images that were already found by another backend are skipped.
+fn interleave(lists: Vec<Vec<Image>>) -> Vec<Image> {
+    let mut seen = Seen::default();
+    let mut merged = Vec::new();
+    let mut lists: Vec<_> = lists.into_iter().map(Vec::into_iter).collect();
+
+    loop {
+        let mut exhausted = true;
+        for list in lists.iter_mut() {
+            if let Some(image) = list.next() {
+                exhausted = false;
+                if seen.insert(&image) {
+                    merged.push(image);
+                }
+            }
+        }
+
+        if exhausted {
+            break merged;
+        }
     }
 }
 
-/// Search for images based on the provided arguments and return the urls of the images
+/// Keeps track of the images that have already been found, by normalized url and thumbnail.
+#[derive(Default)]
+struct Seen {
+    urls: HashSet<String>,
+    thumbnails: HashSet<String>,
+}
+
+impl Seen {
+    /// Returns `true` if neither the url nor the thumbnail of the image has been seen before.
+    fn insert(&mut self, image: &Image) -> bool {
+        let url = normalize(&image.url);
+        let thumbnail = normalize(&image.thumbnail);
+
+        if self.urls.contains(&url)
+            || (!thumbnail.is_empty() && self.thumbnails.contains(&thumbnail))
+        {
+            return false;
+        }
+
+        self.urls.insert(url);
+        if !thumbnail.is_empty() {
+            self.thumbnails.insert(thumbnail);
+        }
+
+        true
+    }
+}
+
+/// Strips the parts of a url that don't change the resource it points to: the scheme, a leading `www.`, the fragment and a trailing slash.
+/// The host is also lowercased.
+fn normalize(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split('#').next().unwrap_or(url);
+    let url = match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None => url,
+    };
+
+    let (host, path) = match url.find('/') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    host.to_owned() + path.trim_end_matches('/')
+}
+
+/// State carried between the pages of a search.
+struct Pages {
+    client: surf::Client,
+    args: Arguments,
+    backend: Arc<dyn SearchBackend>,
+    seen: Seen,
+    offset: usize,
+    page: usize,
+    done: bool,
+}
+
+/// Lazily requests result pages from `backend`, yielding the images on each page that haven't been seen on an earlier one.
+/// The stream ends when a page has no new images or `MAX_PAGES` is reached, or after yielding the error a page failed with.
+///
+/// A later page without any images isn't an error, since search engines answer an offset past the last result with one.
+fn pages(
+    client: surf::Client,
+    args: Arguments,
+    backend: Arc<dyn SearchBackend>,
+) -> impl Stream<Item = SearchResult<Vec<Image>>> {
+    let state = Pages {
+        client,
+        args,
+        backend,
+        seen: Seen::default(),
+        offset: 0,
+        page: 0,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done || state.page >= MAX_PAGES {
+            return None;
+        }
+
+        let first = state.page == 0;
+        state.page += 1;
+
+        if first {
+            if let Err(err) = state.args.validate() {
+                state.done = true;
+                return Some((Err(err), state));
+            }
+        }
+
+        // Spawned since the stream may be polled outside of a runtime the hyper client can use.
+        let url = state.backend.build_url(&state.args, state.offset);
+        let request = get_retrying(state.client.clone(), url, state.args.retry.clone());
+        let found = match async_std::task::spawn(request).await {
+            Ok((status, body)) => state
+                .backend
+                .parse(&body)
+                .map(|parsed| {
+                    if let Some(on_page) = &state.args.on_page {
+                        (on_page.0)(&PageReport {
+                            backend: state.backend.name(),
+                            offset: state.offset,
+                            images: parsed.images.len(),
+                            skipped: parsed.skipped,
+                            strategy: parsed.strategy,
+                        });
+                    }
+                    parsed.images
+                })
+                .map_err(|stage| {
+                    let snapshot = state
+                        .args
+                        .snapshot
+                        .clone()
+                        .filter(|path| std::fs::write(path, &body).is_ok());
+
+                    Error::Parse(ParseError {
+                        stage,
+                        status: Some(status),
+                        snapshot,
+                    })
+                }),
+            Err(err) => Err(err),
+        };
+
+        let found = match found {
+            Ok(found) => found,
+            Err(Error::Parse(ParseError {
+                stage: ParseStage::NoEntries,
+                ..
+            })) if !first => return None,
+            Err(err) => {
+                state.done = true;
+                return Some((Err(err), state));
+            }
+        };
+
+        state.offset += found.len();
+        let new: Vec<Image> = found
+            .into_iter()
+            .filter(|image| state.seen.insert(image))
+            .collect();
+
+        if new.is_empty() {
+            return None;
+        }
+
+        Some((Ok(new), state))
+    })
+}
+
+/// Parse the images out of a Google Images results page that was already fetched, without making any requests.
+///
+/// This is useful for pages saved from earlier crawls or fetched through another HTTP client.
+/// Along with the images, the returned [`Parsed`] reports which [`Strategy`] found them and how many malformed results were skipped.
+/// Pages from other search engines can be parsed with [`SearchBackend::parse`] on the matching backend.
+///
+/// # Errors
+/// This function will return an error if the images are not able to be parsed
+///
+/// # Examples
+///
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{parse_results, Strategy};
+///
+/// let html = std::fs::read_to_string("results.html").unwrap_or_default();
+/// match parse_results(&html) {
+///     Ok(parsed) => {
+///         if parsed.strategy != Strategy::Script {
+///             eprintln!("fell back to {:?}", parsed.strategy);
+///         }
+///         println!("found {} images, skipped {}", parsed.images.len(), parsed.skipped);
+///     }
+///     Err(err) => eprintln!("{}", err),
+/// }
+/// ```
+pub fn parse_results(html: &str) -> SearchResult<Parsed> {
+    Google.parse(html).map_err(|stage| {
+        Error::Parse(ParseError {
+            stage,
+            status: None,
+            snapshot: None,
+        })
+    })
+}
+
+/// Search for images based on the provided arguments and return the urls of the images,
+/// as many as the limit or every one Google has if the limit is 0.
 ///
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// extern crate tokio;
 /// extern crate image_search;
 ///
@@ -584,7 +2025,7 @@ async fn _search(args: Arguments) -> SearchResult<Vec<Image>> {
 ///     Ok(())
 /// }
 pub async fn urls(args: Arguments) -> SearchResult<Vec<String>> {
-    let thumbnails = (&args.thumbnails).to_owned();
+    let thumbnails = args.thumbnails;
     let images = search(args).await?;
 
     let mut all: Vec<String> = Vec::new();
@@ -604,12 +2045,13 @@ pub async fn urls(args: Arguments) -> SearchResult<Vec<String>> {
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 /// * The program is unable to create/read/write to files or directories
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// extern crate tokio;
 /// extern crate image_search;
 ///
@@ -619,11 +2061,12 @@ pub async fn urls(args: Arguments) -> SearchResult<Vec<String>> {
 /// #[tokio::main]
 /// async fn main() -> Result<(), image_search::Error> {
 ///     let args = Arguments::new("example", 10).directory(Path::new("downloads"));
-///     let paths = image_search::download(args).await?;
+///     let report = image_search::download(args).await?;
+///     println!("saved {} of 10 images", report.paths().len());
 ///
 ///     Ok(())
 /// }
-pub async fn download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
+pub async fn download(args: Arguments) -> SearchResult<DownloadReport> {
     async_std::task::spawn(_download(args)).await
 }
 
@@ -635,19 +2078,33 @@ pub async fn download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
 /// # Errors
 /// This function will return an error if:
 /// * The GET request fails
+/// * The search engine blocks the request with a consent page, a CAPTCHA or a rate limit
 /// * The images are not able to be parsed
 /// * The program is unable to create/read/write to files or directories
-async fn _download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
-    let images = urls(Arguments {
-        query: args.query.clone(),
-        limit: 0,
-        directory: args.directory.clone(),
-        ..args
-    })
-    .await?;
+pub(crate) async fn _download(args: Arguments) -> SearchResult<DownloadReport> {
+    let downloader = Downloader::new(
+        Some(args.concurrency),
+        Some(args.host_concurrency),
+        None,
+        args.politeness.clone(),
+    );
+    download_with(&downloader, args).await
+}
 
-    let dir = match args.directory {
-        Some(dir) => dir.to_owned(),
+/// Downloads the images for `args`, sharing the client and limits of `downloader` with any other queries using it.
+pub(crate) async fn download_with(
+    downloader: &Downloader,
+    args: Arguments,
+) -> SearchResult<DownloadReport> {
+    // Fetch twice as many candidates as needed so there is something to fall back on when an image fails to download.
+    let permit = downloader.acquire().await;
+    let images = fetch(&downloader.client, &args, args.limit.saturating_mul(2)).await;
+    drop(permit);
+
+    let images = images?;
+
+    let dir = match args.directory.clone() {
+        Some(dir) => dir,
         None => match env::current_dir() {
             Ok(v) => v,
             Err(e) => return Err(Error::Dir(e)),
@@ -660,10 +2117,11 @@ async fn _download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
         Err(e) => return Err(Error::Dir(e)),
     };
 
+    let name = file_name(&args.query);
     let mut suffix = 0;
     let mut paths: Vec<PathBuf> = Vec::new();
     for _ in 0..args.limit {
-        let mut path = dir.join(args.query.to_owned() + &suffix.to_string());
+        let mut path = dir.join(name.to_owned() + &suffix.to_string());
 
         let mut matches = match glob::glob(&(path.display().to_string() + ".*")) {
             Ok(paths) => paths.last().is_some(),
@@ -672,7 +2130,7 @@ async fn _download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
 
         while matches {
             suffix += 1;
-            path = dir.join(args.query.to_owned() + &suffix.to_string());
+            path = dir.join(name.to_owned() + &suffix.to_string());
             matches = match glob::glob(&(path.display().to_string() + ".*")) {
                 Ok(paths) => paths.last().is_some(),
                 Err(_) => false,
@@ -683,85 +2141,375 @@ async fn _download(args: Arguments) -> SearchResult<Vec<PathBuf>> {
         suffix += 1;
     }
 
-    let with_extensions = download_n(images, paths, args.timeout).await;
+    Ok(download_n(downloader, images, paths, &args).await)
+}
 
-    Ok(with_extensions)
+/// Replaces the characters of a query that aren't allowed in file names, like the `:` in `site:` or the `"` around phrases.
+fn file_name(query: &str) -> String {
+    query
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
 }
 
-/// Trys to download
-async fn download_n(
-    urls: Vec<String>,
-    paths: Vec<PathBuf>,
-    timeout: Option<Duration>,
-) -> Vec<PathBuf> {
-    let mut_urls = Arc::new(Mutex::new(urls));
+/// The client, concurrency limits and rate limits shared by every request made by one call to [`download`] or [`download_batch`].
+pub(crate) struct Downloader {
+    client: surf::Client,
+    permits: Option<Semaphore>,
+    host_concurrency: Option<usize>,
+    politeness: Politeness,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+    robots: Mutex<HashMap<String, Arc<OnceCell<Robots>>>>,
+    rate: Option<RateLimiter>,
+}
 
-    let mut downloaders = Vec::new();
-    let client = surf::Client::new();
-    for path in paths {
-        downloaders.push(download_until(
-            mut_urls.clone(),
-            path,
-            client.clone(),
-            timeout,
-        ));
+/// The limits on requests to one host.
+struct Host {
+    permits: Option<Arc<Semaphore>>,
+    bucket: HostBucket,
+}
+
+impl Downloader {
+    /// Creates a downloader that makes at most `concurrency` requests at once, `host_concurrency` of them to the same host,
+    /// and at most `per_second` requests every second, while treating each host as gently as `politeness` asks.
+    /// Any limit that is `None` is left out.
+    pub(crate) fn new(
+        concurrency: Option<usize>,
+        host_concurrency: Option<usize>,
+        per_second: Option<u32>,
+        politeness: Politeness,
+    ) -> Downloader {
+        Downloader {
+            client: surf::Client::new(),
+            permits: concurrency.map(|concurrency| Semaphore::new(concurrency.max(1))),
+            host_concurrency: host_concurrency.map(|host_concurrency| host_concurrency.max(1)),
+            politeness,
+            hosts: Mutex::new(HashMap::new()),
+            robots: Mutex::new(HashMap::new()),
+            rate: per_second.map(RateLimiter::new),
+        }
     }
 
-    let with_extensions = future::join_all(downloaders)
-        .await
-        .into_iter()
-        .filter_map(|x| x.ok())
-        .collect();
+    /// Waits until another request can be made, returning a guard that holds its place until it's dropped.
+    async fn acquire(&self) -> Option<SemaphoreGuard<'_>> {
+        let permit = match &self.permits {
+            Some(permits) => Some(permits.acquire().await),
+            None => None,
+        };
+        if let Some(rate) = &self.rate {
+            rate.wait().await;
+        }
 
-    with_extensions
-}
+        permit
+    }
+
+    /// Waits until another request can be made to the host of `url`, on top of the limits of [`acquire`](Downloader::acquire).
+    ///
+    /// The host's place is taken first, so a request waiting on a busy host doesn't hold up requests to other hosts.
+    async fn acquire_host(
+        &self,
+        url: &str,
+    ) -> (Option<SemaphoreGuardArc>, Option<SemaphoreGuard<'_>>) {
+        let host = surf::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .map(|host| self.host(host));
+
+        let mut host_permit = None;
+        if let Some(host) = host {
+            if let Some(permits) = &host.permits {
+                host_permit = Some(permits.acquire_arc().await);
+            }
+            host.bucket.wait(&self.politeness).await;
+        }
+        (host_permit, self.acquire().await)
+    }
+
+    /// The limits on requests to `host`, shared by every request made to it.
+    fn host(&self, host: String) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|err| err.into_inner());
+
+        hosts
+            .entry(host)
+            .or_insert_with(|| {
+                Arc::new(Host {
+                    permits: self
+                        .host_concurrency
+                        .map(|host_concurrency| Arc::new(Semaphore::new(host_concurrency))),
+                    bucket: HostBucket::new(&self.politeness),
+                })
+            })
+            .clone()
+    }
+
+    /// Whether the robots.txt of the host of `url` allows `user_agent` to fetch it,
+    /// or the error fetching the robots.txt failed with.
+    ///
+    /// Each host's robots.txt is only fetched once, however many images are waiting on it.
+    /// One that couldn't be fetched is tried again for the next image from the host.
+    async fn robots_allow(
+        &self,
+        url: &surf::Url,
+        user_agent: &str,
+        args: &Arguments,
+    ) -> Result<bool, DownloadError> {
+        let origin = url.origin().ascii_serialization();
+
+        let robots = {
+            let mut robots = self.robots.lock().unwrap_or_else(|err| err.into_inner());
+            robots.entry(origin.clone()).or_default().clone()
+        };
+        let robots = robots
+            .get_or_try_init(|| {
+                self.fetch_robots(format!("{}/robots.txt", origin), user_agent, args)
+            })
+            .await?;
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        Ok(robots.allows(user_agent, &path))
+    }
+
+    /// Fetches and reads a robots.txt, retrying it like an image.
+    ///
+    /// Following RFC 9309, up to 5 redirects are followed and a file that's missing allows everything.
+    /// One that can't be reached is an error, so nothing is downloaded from the host.
+    async fn fetch_robots(
+        &self,
+        url: String,
+        user_agent: &str,
+        args: &Arguments,
+    ) -> Result<Robots, DownloadError> {
+        let fetch = || async {
+            let _permits = self.acquire_host(&url).await;
+            let request = async {
+                let mut url = url.clone();
+                let mut redirects = 0;
+                loop {
+                    let mut response = self
+                        .client
+                        .get(&url)
+                        .header("User-Agent", user_agent)
+                        .await?;
+                    let status: u16 = response.status().into();
+                    let location = response.header("Location").and_then(|location| {
+                        surf::Url::parse(&url).ok()?.join(location.as_str()).ok()
+                    });
+
+                    match (status, location) {
+                        (300..=399, Some(location)) if redirects < 5 => {
+                            url = location.into();
+                            redirects += 1;
+                        }
+                        (200..=299, _) => return Ok((status, response.body_string().await?)),
+                        _ => return Ok::<_, DownloadError>((status, String::new())),
+                    }
+                }
+            };
+
+            match args.timeout {
+                Some(duration) => async_std::future::timeout(duration, request).await?,
+                None => request.await,
+            }
+        };
 
-macro_rules! next_available {
-    ($urls:expr) => {{
-        let mut mut_urls = $urls.lock().expect("Other downloading thread panicked"); // SAFETY: no thread should panic while holding, since this is the only unwrap/expect
-        if mut_urls.is_empty() {
-            return Err(DownloadError::Overflow);
+        let response = args
+            .retry
+            .run(fetch, |result| match result {
+                Ok((status, _)) if *status == 429 || *status >= 500 => {
+                    Some((FailureKind::from_status(*status), None))
+                }
+                Ok(_) => None,
+                Err(err) => err.failure(),
+            })
+            .await;
+
+        match response? {
+            (200..=299, body) => Ok(Robots::parse(&body)),
+            (status @ (429 | 500..), _) => Err(DownloadError::Status {
+                status,
+                retry_after: None,
+            }),
+            // Too many redirects count as a missing file.
+            _ => Ok(Robots::allow_all()),
         }
-        let url = mut_urls.remove(0);
-        std::mem::drop(mut_urls);
+    }
+}
 
-        url
-    }};
+/// Spaces requests out evenly so no more than a set amount are started every second.
+struct RateLimiter {
+    interval: Duration,
+    next: async_std::sync::Mutex<Instant>,
 }
 
-async fn download_until(
-    urls: Arc<Mutex<Vec<String>>>,
-    path: PathBuf,
-    client: surf::Client,
-    timeout: Option<Duration>,
-) -> Result<PathBuf, DownloadError> {
-    let mut url = next_available!(urls);
+impl RateLimiter {
+    fn new(per_second: u32) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs(1) / per_second.max(1),
+            next: async_std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let start = {
+            let mut next = self.next.lock().await;
+            let start = (*next).max(Instant::now());
+            *next = start + self.interval;
+            start
+        };
 
-    let with_extension = loop {
-        let path = download_image(client.clone(), &path, url.to_owned(), timeout).await;
-        if path.is_ok() {
-            break path;
+        let now = Instant::now();
+        if start > now {
+            async_std::task::sleep(start - now).await;
         }
-        url = next_available!(urls);
-    };
+    }
+}
+
+/// Downloads an image to each of `paths`, falling back on the next image whenever one fails.
+///
+/// The paths are shared between `args.concurrency` workers, each downloading one image at a time,
+/// so only that many images are ever downloaded at once.
+async fn download_n(
+    downloader: &Downloader,
+    images: Vec<Image>,
+    paths: Vec<PathBuf>,
+    args: &Arguments,
+) -> DownloadReport {
+    let workers = args.concurrency.min(paths.len());
+    let images: Scheduler<(usize, Image)> = images.into_iter().enumerate().collect();
+    let paths: Scheduler<PathBuf> = paths.into_iter().collect();
+
+    let workers = (0..workers).map(|_| async {
+        let mut reports = Vec::new();
+        'paths: while let Some(path) = paths.pop() {
+            while let Some((rank, image)) = images.pop() {
+                let report = download_image(downloader, &path, image, args).await;
+                let saved = report.result.is_ok();
+                reports.push((rank, report));
+
+                if saved {
+                    continue 'paths;
+                }
+            }
+            // Every image has been tried, so there's nothing left for the other paths either.
+            break;
+        }
+        reports
+    });
 
-    with_extension
+    let mut reports: Vec<(usize, ImageReport)> = future::join_all(workers)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    reports.sort_by_key(|(rank, _)| *rank);
+
+    DownloadReport {
+        images: reports.into_iter().map(|(_, report)| report).collect(),
+    }
 }
 
+/// Downloads `image` to `path`, retrying it as set by [`Arguments::retry`] before it's given up on.
 async fn download_image(
-    client: surf::Client,
-    path: &PathBuf,
-    url: String,
-    timeout: Option<Duration>,
-) -> Result<PathBuf, DownloadError> {
-    let buf = match timeout {
-        Some(duration) => {
-            async_std::future::timeout(duration, client.recv_bytes(surf::get(url))).await?
+    downloader: &Downloader,
+    path: &Path,
+    image: Image,
+    args: &Arguments,
+) -> ImageReport {
+    let start = Instant::now();
+    let url = if args.thumbnails {
+        image.thumbnail.clone()
+    } else {
+        image.url.clone()
+    };
+
+    // Checked before the first attempt, since the client panics on a url it can't parse.
+    let allowed = match surf::Url::parse(&url) {
+        Err(_) => Err(DownloadError::InvalidUrl(url.clone())),
+        Ok(parsed) => match &args.robots {
+            Some(user_agent) => match downloader.robots_allow(&parsed, user_agent, args).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(DownloadError::Skipped(SkipReason::RobotsDisallowed)),
+                Err(err) => Err(err),
+            },
+            None => Ok(()),
+        },
+    };
+    if let Err(err) = allowed {
+        return ImageReport {
+            image,
+            result: Err(err),
+            bytes: 0,
+            duration: start.elapsed(),
+            attempts: 0,
+        };
+    }
+
+    let mut attempts = 0;
+    let (result, bytes) = args
+        .retry
+        .run(
+            || {
+                attempts += 1;
+                async {
+                    match fetch_image(downloader, &url, args).await {
+                        Ok(buf) => (save_image(path, &buf), buf.len() as u64),
+                        Err(err) => (Err(err), 0),
+                    }
+                }
+            },
+            |(result, _)| result.as_ref().err().and_then(DownloadError::failure),
+        )
+        .await;
+
+    ImageReport {
+        image,
+        result,
+        bytes,
+        duration: start.elapsed(),
+        attempts,
+    }
+}
+
+/// Makes one request for the image at `url`, returning its body.
+async fn fetch_image(
+    downloader: &Downloader,
+    url: &str,
+    args: &Arguments,
+) -> Result<Vec<u8>, DownloadError> {
+    let _permits = downloader.acquire_host(url).await;
+    let request = async {
+        let mut request = downloader.client.get(url);
+        if let Some(user_agent) = &args.robots {
+            request = request.header("User-Agent", user_agent.as_str());
+        }
+        let mut response = request.await?;
+        if !response.status().is_success() {
+            return Err(DownloadError::Status {
+                status: response.status().into(),
+                retry_after: retry_after(
+                    response.header("Retry-After").map(|value| value.as_str()),
+                ),
+            });
         }
-        None => client.recv_bytes(surf::get(url)).await,
-    }?;
+        Ok(response.body_bytes().await?)
+    };
 
-    let first_128 = buf.iter().take(1024).map(|x| *x).collect::<Vec<u8>>();
+    match args.timeout {
+        Some(duration) => async_std::future::timeout(duration, request).await?,
+        None => request.await,
+    }
+}
+
+/// Saves an image to `path`, with the extension of the type of image it is.
+fn save_image(path: &Path, buf: &[u8]) -> Result<PathBuf, DownloadError> {
+    let first_128 = buf.iter().take(1024).copied().collect::<Vec<u8>>();
     let svg = match std::str::from_utf8(&first_128) {
         Ok(s) => s.contains("<svg"),
         Err(_) => false,
@@ -770,26 +2518,26 @@ async fn download_image(
     let extension = if svg {
         "svg".to_owned()
     } else {
-        let kind = match infer::get(&buf) {
+        let kind = match infer::get(buf) {
             Some(k) => k,
-            None => return Err(DownloadError::Extension),
+            None => return Err(DownloadError::NotAnImage),
         };
 
         if kind.matcher_type() != infer::MatcherType::Image {
-            return Err(DownloadError::Extension);
+            return Err(DownloadError::NotAnImage);
         }
 
         kind.extension().to_owned()
     };
 
-    let with_extension = path.clone().with_extension(extension);
+    let with_extension = path.with_extension(extension);
 
     let mut f = match File::create(&with_extension) {
         Ok(f) => f,
         Err(e) => return Err(DownloadError::Fs(e)),
     };
 
-    match f.write_all(&buf) {
+    match f.write_all(buf) {
         Ok(_) => (),
         Err(e) => return Err(DownloadError::Fs(e)),
     };
@@ -797,83 +2545,82 @@ async fn download_image(
     Ok(with_extension)
 }
 
-pub(crate) fn build_url(args: &Arguments) -> String {
-    let mut url = "https://www.google.com/search?udm=2&q=".to_string() + &args.query;
-
-    let params = args.params();
-    if params.len() > 0 {
-        url += &"&tbs=ic:specific".to_string();
-        url += &params;
-    }
-
-    url
+/// Makes a GET request to `url` like [`get`], retrying it as set by `retry`.
+///
+/// Server errors are retried along with network errors and rate limits, since they're usually gone by the next attempt.
+async fn get_retrying(
+    client: surf::Client,
+    url: String,
+    retry: RetryPolicy,
+) -> Result<(u16, String), Error> {
+    retry
+        .run(
+            || get(&client, url.clone()),
+            |result| match result {
+                Ok((status, _)) if *status >= 500 => Some((FailureKind::ServerError, None)),
+                Ok(_) => None,
+                Err(Error::Network(_)) => Some((FailureKind::Network, None)),
+                Err(Error::RateLimited { retry_after }) => {
+                    Some((FailureKind::RateLimited, *retry_after))
+                }
+                Err(_) => None,
+            },
+        )
+        .await
 }
 
-async fn get(url: String) -> Result<String, surf::Error> {
-    Ok(surf::get(url)
+/// Makes a GET request to `url` with `client`, returning the status and body of the response.
+///
+/// Responses that block the search instead of answering it, like consent pages, CAPTCHAs and rate limits, are returned as errors.
+async fn get(client: &surf::Client, url: String) -> Result<(u16, String), Error> {
+    let mut response = client
+        .get(url)
         .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/88.0.4324.104 Safari/537.36")
-        .recv_string()
-        .await?)
-}
-
-/// shorthand for unwrap_or_continue
-macro_rules! uoc {
-    ($opt: expr) => {
-        match $opt {
-            Some(v) => v,
-            None => {
-                continue;
-            }
-        }
-    };
+        .await?;
+    let body = response.body_string().await?;
+    let status: u16 = response.status().into();
+
+    let location = response.header("Location").map(|value| value.as_str());
+    let retry_after = response.header("Retry-After").map(|value| value.as_str());
+    match classify(status, location, retry_after, &body) {
+        Some(err) => Err(err),
+        None => Ok((status, body)),
+    }
 }
 
-pub(crate) fn unpack(recv: String) -> Option<Vec<Image>> {
-    let start = recv.find("var m={")? + "var m=".len();
-    let mut body = &recv[start..];
-
-    let script_end = body.find("var a=m")?;
-    body = &body[..script_end];
-
-    let end = body.rfind(";")?;
-    body = &body[..end];
-
-    let json: serde_json::Value = serde_json::from_str(&body).ok()?;
-
-    let image_objects = json
-        .as_object()?
-        .values()
-        .filter(|list| {
-            list.as_array()
-                .map(|list| {
-                    list.get(0).map(|value| value.is_u64()).unwrap_or(false)
-                        && list.get(1).map(|value| value.is_array()).unwrap_or(false)
-                })
-                .unwrap_or(false)
-        })
-        .map(|image| image.as_array().unwrap()[1].as_array().unwrap());
-
-    let mut images: Vec<Image> = Vec::new();
-    for obj in image_objects {
-        let (url, width, height) = match obj[3].as_array() {
-            Some(i) => (
-                uoc!(i[0].as_str()).to_string(),
-                uoc!(i[1].as_i64()),
-                uoc!(i[2].as_i64()),
-            ),
-            None => continue,
-        };
+/// Recognizes the pages search engines send instead of results when they won't answer a request.
+fn classify(
+    status: u16,
+    location: Option<&str>,
+    retry_after: Option<&str>,
+    body: &str,
+) -> Option<Error> {
+    let location = location.unwrap_or_default();
+
+    if location.contains("consent.google.") || body.contains("action=\"https://consent.google.") {
+        return Some(Error::Consent);
+    }
 
-        let image = Image {
-            url,
-            width,
-            height,
-            thumbnail: uoc!(uoc!(obj[2].as_array())[0].as_str()).to_string(),
-            source: uoc!(uoc!(uoc!(obj[9].as_object())["2003"].as_array())[2].as_str()).to_string(),
-        };
+    if location.contains("/sorry/")
+        || body.contains("/sorry/index")
+        || body.contains("unusual traffic from your computer network")
+        || body.contains("g-recaptcha")
+    {
+        return Some(Error::Captcha);
+    }
 
-        images.push(image);
+    if status == 429 {
+        return Some(Error::RateLimited {
+            retry_after: self::retry_after(retry_after),
+        });
     }
 
-    Some(images)
+    None
+}
+
+/// Reads a `Retry-After` header given in seconds. Dates aren't supported, and are read as `None`.
+fn retry_after(value: Option<&str>) -> Option<Duration> {
+    value
+        .and_then(|seconds| seconds.trim().parse().ok())
+        .map(Duration::from_secs)
 }
@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+/// How gently each image host is treated, on top of the limits set by [`Arguments::host_concurrency`](crate::Arguments::host_concurrency).
+///
+/// A handful of hosts make up most of the results, and many of them ban clients that open too many connections or request too much too quickly.
+/// Every host gets a token bucket that lets a few requests through at once and then settles down to a steady rate,
+/// and requests to the same host are never started closer together than the crawl delay.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, Politeness};
+/// use std::time::Duration;
+///
+/// let politeness = Politeness::new()
+///     .rate_limit(1.0)
+///     .burst(2)
+///     .crawl_delay(Duration::from_millis(500));
+/// let args = Arguments::new("cats", 10).politeness(politeness);
+///
+/// assert_eq!(args.get_politeness().get_rate_limit(), Some(1.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "PolitenessFields")
+)]
+pub struct Politeness {
+    rate_limit: Option<f64>,
+    burst: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::duration"))]
+    crawl_delay: Duration,
+}
+
+impl Politeness {
+    /// Creates limits of 4 requests a second to each host, with bursts of up to 4 requests spaced at least 100 milliseconds apart.
+    pub fn new() -> Politeness {
+        Politeness {
+            rate_limit: Some(4.0),
+            burst: 4,
+            crawl_delay: Duration::from_millis(100),
+        }
+    }
+
+    /// Creates limits that let requests through to every host as fast as they are made.
+    pub fn unlimited() -> Politeness {
+        Politeness {
+            rate_limit: None,
+            burst: 1,
+            crawl_delay: Duration::ZERO,
+        }
+    }
+
+    /// Sets the most requests started every second to the same host, averaged over time. Defaults to 4.
+    /// `None`, or a rate that isn't a positive number, removes the limit.
+    pub fn rate_limit<R: Into<Option<f64>>>(mut self, per_second: R) -> Self {
+        self.rate_limit = per_second.into().filter(|rate| valid_rate(*rate));
+        self
+    }
+
+    /// Sets the most requests that can be started to the same host at once after it's been left alone for a while. Defaults to 4.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Sets the least time between the start of two requests to the same host. Defaults to 100 milliseconds.
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.crawl_delay = delay;
+        self
+    }
+
+    pub fn get_rate_limit(&self) -> Option<f64> {
+        self.rate_limit
+    }
+
+    pub fn get_burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn get_crawl_delay(&self) -> Duration {
+        self.crawl_delay
+    }
+}
+
+impl Default for Politeness {
+    fn default() -> Self {
+        Politeness::new()
+    }
+}
+
+/// Whether a rate limit can be waited on, which rules out zero, negative rates, infinity and NaN.
+fn valid_rate(rate: f64) -> bool {
+    rate.is_finite() && rate > 0.0
+}
+
+/// The fields of a [`Politeness`] as they are written, which are read back through its builders
+/// so that a rate or burst they would reject can't be loaded either.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct PolitenessFields {
+    rate_limit: Option<f64>,
+    burst: u32,
+    #[serde(with = "crate::serialize::duration")]
+    crawl_delay: Duration,
+}
+
+#[cfg(feature = "serde")]
+impl Default for PolitenessFields {
+    fn default() -> Self {
+        let politeness = Politeness::new();
+        PolitenessFields {
+            rate_limit: politeness.rate_limit,
+            burst: politeness.burst,
+            crawl_delay: politeness.crawl_delay,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<PolitenessFields> for Politeness {
+    fn from(fields: PolitenessFields) -> Self {
+        Politeness::new()
+            .rate_limit(fields.rate_limit)
+            .burst(fields.burst)
+            .crawl_delay(fields.crawl_delay)
+    }
+}
+
+/// The token bucket and crawl delay of one host.
+///
+/// Each request reserves its start time while the bucket is locked and then waits for it unlocked,
+/// so requests are let through in the order they arrived without holding each other up.
+pub(crate) struct HostBucket {
+    state: async_std::sync::Mutex<BucketState>,
+}
+
+struct BucketState {
+    /// Goes below zero when requests have reserved tokens that haven't been refilled yet.
+    tokens: f64,
+    updated: Instant,
+    last: Option<Instant>,
+}
+
+impl HostBucket {
+    pub(crate) fn new(politeness: &Politeness) -> HostBucket {
+        HostBucket {
+            state: async_std::sync::Mutex::new(BucketState {
+                tokens: f64::from(politeness.burst),
+                updated: Instant::now(),
+                last: None,
+            }),
+        }
+    }
+
+    /// Waits until a request can be started to this host.
+    pub(crate) async fn wait(&self, politeness: &Politeness) {
+        let start = {
+            let mut state = self.state.lock().await;
+            let now = Instant::now();
+            let mut start = now;
+
+            // Checked again here, since a rate that isn't positive and finite would wait forever.
+            if let Some(rate) = politeness.rate_limit.filter(|rate| valid_rate(*rate)) {
+                let refilled = (now - state.updated).as_secs_f64() * rate;
+                state.tokens = (state.tokens + refilled).min(f64::from(politeness.burst));
+                state.updated = now;
+
+                if state.tokens < 1.0 {
+                    start += Duration::from_secs_f64((1.0 - state.tokens) / rate);
+                }
+                state.tokens -= 1.0;
+            }
+            if let Some(last) = state.last {
+                start = start.max(last + politeness.crawl_delay);
+            }
+
+            state.last = Some(start);
+            start
+        };
+
+        let now = Instant::now();
+        if start > now {
+            async_std::task::sleep(start - now).await;
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+
+/// Builds a search query out of terms and search operators.
+///
+/// The query is only rendered into text here, and is percent-encoded along with the rest of the url when the search is made,
+/// so terms can safely contain characters like `&`, `#`, `+` or non-ASCII text.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, Query};
+///
+/// let query = Query::new()
+///     .term("cat")
+///     .phrase("black and white")
+///     .exclude("dog")
+///     .any_of(["kitten", "kitty"])
+///     .site("example.com")
+///     .exclude_site("pinterest.com")
+///     .filetype("png");
+///
+/// assert_eq!(
+///     query.to_string(),
+///     r#"cat "black and white" -dog (kitten OR kitty) site:example.com -site:pinterest.com filetype:png"#
+/// );
+///
+/// let args = Arguments::new(query, 10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    parts: Vec<String>,
+}
+
+impl Query {
+    pub fn new() -> Query {
+        Query::default()
+    }
+
+    /// Adds keywords that the results should match.
+    pub fn term<S: AsRef<str>>(mut self, term: S) -> Self {
+        let term = term.as_ref().trim();
+        if !term.is_empty() {
+            self.parts.push(term.to_owned());
+        }
+        self
+    }
+
+    /// Adds a phrase that the results must match exactly.
+    pub fn phrase<S: AsRef<str>>(mut self, phrase: S) -> Self {
+        let phrase = quote(phrase.as_ref());
+        if !phrase.is_empty() {
+            self.parts.push(phrase);
+        }
+        self
+    }
+
+    /// Excludes results matching a word, or a phrase if it contains spaces.
+    pub fn exclude<S: AsRef<str>>(mut self, term: S) -> Self {
+        let term = term.as_ref().trim();
+        if term.contains(char::is_whitespace) {
+            self.parts.push(format!("-{}", quote(term)));
+        } else if !term.is_empty() {
+            self.parts.push(format!("-{}", term));
+        }
+        self
+    }
+
+    /// Adds a group of terms, of which the results only need to match one.
+    pub fn any_of<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let terms: Vec<String> = terms
+            .into_iter()
+            .map(|term| {
+                let term = term.as_ref().trim();
+                if term.contains(char::is_whitespace) {
+                    quote(term)
+                } else {
+                    term.to_owned()
+                }
+            })
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        match terms.len() {
+            0 => (),
+            1 => self.parts.extend(terms),
+            _ => self.parts.push(format!("({})", terms.join(" OR "))),
+        }
+        self
+    }
+
+    /// Only finds images from a site or domain.
+    pub fn site<S: AsRef<str>>(self, domain: S) -> Self {
+        self.operator("site:", domain.as_ref())
+    }
+
+    /// Leaves out images from a site or domain.
+    pub fn exclude_site<S: AsRef<str>>(self, domain: S) -> Self {
+        self.operator("-site:", domain.as_ref())
+    }
+
+    /// Only finds images of a file type, such as `png`.
+    pub fn filetype<S: AsRef<str>>(self, extension: S) -> Self {
+        self.operator("filetype:", extension.as_ref().trim_start_matches('.'))
+    }
+
+    fn operator(mut self, operator: &str, value: &str) -> Self {
+        let value: String = value.split_whitespace().collect();
+        if !value.is_empty() {
+            self.parts.push(format!("{}{}", operator, value));
+        }
+        self
+    }
+}
+
+/// Wraps a phrase in quotes, removing any quotes inside of it since they can't be escaped.
+fn quote(phrase: &str) -> String {
+    let phrase = phrase.replace('"', "");
+    let phrase = phrase.trim();
+
+    if phrase.is_empty() {
+        String::new()
+    } else {
+        format!("\"{}\"", phrase)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.parts.join(" "))
+    }
+}
+
+impl From<Query> for String {
+    fn from(query: Query) -> Self {
+        query.to_string()
+    }
+}
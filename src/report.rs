@@ -0,0 +1,98 @@
+use crate::{DownloadError, Image, Strategy};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What happened to every image [`download`](crate::download) tried, so it's clear why fewer images were saved than asked for.
+///
+/// Images are listed in the order they were ranked, and only the ones that were tried are listed.
+/// An image that fails is replaced by the next one that hasn't been tried yet, so both of them are listed.
+///
+/// # Example
+/// ```no_run
+/// extern crate tokio;
+/// extern crate image_search;
+///
+/// use image_search::Arguments;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), image_search::Error> {
+///     let report = image_search::download(Arguments::new("cats", 10)).await?;
+///
+///     println!("saved {} images", report.paths().len());
+///     for failure in report.failures() {
+///         if let Err(err) = &failure.result {
+///             eprintln!("{}: {}", failure.image.url, err);
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct DownloadReport {
+    pub images: Vec<ImageReport>,
+}
+
+impl DownloadReport {
+    /// The paths the images were saved to.
+    pub fn paths(&self) -> Vec<&Path> {
+        self.images
+            .iter()
+            .filter_map(|image| image.result.as_deref().ok())
+            .collect()
+    }
+
+    /// The paths the images were saved to, dropping the rest of the report.
+    pub fn into_paths(self) -> Vec<PathBuf> {
+        self.images
+            .into_iter()
+            .filter_map(|image| image.result.ok())
+            .collect()
+    }
+
+    /// The images that weren't saved, skipped ones included.
+    pub fn failures(&self) -> impl Iterator<Item = &ImageReport> {
+        self.images.iter().filter(|image| image.result.is_err())
+    }
+}
+
+/// What happened to one image that [`download`](crate::download) tried.
+#[derive(Debug)]
+pub struct ImageReport {
+    pub image: Image,
+    /// The path the image was saved to, or why it wasn't after its last attempt.
+    pub result: Result<PathBuf, DownloadError>,
+    /// The size of the response to the last attempt, or 0 if there wasn't one.
+    pub bytes: u64,
+    /// The time from the first attempt to the end of the last, including any waits for limits or retries.
+    pub duration: Duration,
+    /// The number of times the image was requested, which is 0 if it never was.
+    pub attempts: u32,
+}
+
+/// How one page of search results was parsed, passed to the callback set with [`Arguments::on_page`](crate::Arguments::on_page).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageReport {
+    /// The name of the backend the page came from.
+    pub backend: &'static str,
+    /// The offset the page was requested at.
+    pub offset: usize,
+    /// The number of images on the page, including any that were already found on an earlier one.
+    pub images: usize,
+    /// The number of results on the page that were malformed and skipped.
+    pub skipped: usize,
+    /// How the images were found in the page.
+    pub strategy: Strategy,
+}
+
+/// The callback set with [`Arguments::on_page`](crate::Arguments::on_page).
+#[derive(Clone)]
+pub(crate) struct OnPage(pub(crate) Arc<dyn Fn(&PageReport) + Send + Sync>);
+
+impl fmt::Debug for OnPage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OnPage")
+    }
+}
@@ -0,0 +1,202 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// The ways a request can fail, used to decide which failures are worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    /// The request timed out.
+    Timeout,
+    /// The connection couldn't be made or was dropped.
+    Network,
+    /// The server answered with a 5xx status, or with 408 Request Timeout.
+    ServerError,
+    /// The server answered with 429 Too Many Requests.
+    RateLimited,
+    /// The server answered with any other status that isn't a success, such as 404 Not Found.
+    ClientError,
+    /// The response wasn't an image.
+    NotAnImage,
+    /// The image couldn't be written to disk.
+    Fs,
+}
+
+impl FailureKind {
+    pub const ALL: [FailureKind; 7] = [
+        FailureKind::Timeout,
+        FailureKind::Network,
+        FailureKind::ServerError,
+        FailureKind::RateLimited,
+        FailureKind::ClientError,
+        FailureKind::NotAnImage,
+        FailureKind::Fs,
+    ];
+
+    /// The kind of failure an unsuccessful HTTP status is.
+    pub(crate) fn from_status(status: u16) -> FailureKind {
+        match status {
+            429 => FailureKind::RateLimited,
+            408 | 500..=599 => FailureKind::ServerError,
+            _ => FailureKind::ClientError,
+        }
+    }
+}
+
+/// How many times a request is made before giving up on it, and how long to wait in between.
+///
+/// Used for each image before falling back on the next one, and for each page of search results.
+/// The wait doubles after every failed attempt, up to a maximum, and is randomized by up to half
+/// so that requests that failed together don't all retry at the same moment.
+/// A server that asks to be retried later, with a `Retry-After` header, is never retried sooner than it asked,
+/// and is given up on straight away if it asks for a longer wait than the maximum.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::{Arguments, FailureKind, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let retry = RetryPolicy::new()
+///     .attempts(5)
+///     .backoff(Duration::from_millis(200), Duration::from_secs(5))
+///     .retry_on([FailureKind::Timeout, FailureKind::ServerError]);
+/// let args = Arguments::new("cats", 10).retry(retry);
+///
+/// assert_eq!(args.get_retry().get_attempts(), 5);
+/// assert!(!args.get_retry().retries(FailureKind::Network));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RetryPolicy {
+    attempts: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::duration"))]
+    backoff: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::duration"))]
+    max_backoff: Duration,
+    jitter: bool,
+    retry_on: Vec<FailureKind>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that makes up to 3 attempts, waiting half a second before the first retry and at most 10 seconds before any other,
+    /// and that retries timeouts, network errors, server errors and rate limits.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_on: vec![
+                FailureKind::Timeout,
+                FailureKind::Network,
+                FailureKind::ServerError,
+                FailureKind::RateLimited,
+            ],
+        }
+    }
+
+    /// Creates a policy that makes every request only once.
+    pub fn never() -> RetryPolicy {
+        RetryPolicy::new().attempts(1)
+    }
+
+    /// Sets the most times a request is made, including the first. Defaults to 3.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the wait before the first retry, which doubles after every retry up to `max`.
+    /// `max` is also the longest a server can ask to wait with `Retry-After` before it's given up on.
+    /// Defaults to half a second, up to 10 seconds.
+    pub fn backoff(mut self, first: Duration, max: Duration) -> Self {
+        self.backoff = first;
+        self.max_backoff = max.max(first);
+        self
+    }
+
+    /// Determines whether the waits are randomized. Defaults to `true`.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the kinds of failures that are retried, replacing the defaults.
+    pub fn retry_on<I: IntoIterator<Item = FailureKind>>(mut self, kinds: I) -> Self {
+        self.retry_on = kinds.into_iter().collect();
+        self
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn get_backoff(&self) -> (Duration, Duration) {
+        (self.backoff, self.max_backoff)
+    }
+
+    pub fn get_jitter(&self) -> bool {
+        self.jitter
+    }
+
+    pub fn get_retry_on(&self) -> &[FailureKind] {
+        &self.retry_on
+    }
+
+    /// Whether failures of this kind are retried.
+    pub fn retries(&self, kind: FailureKind) -> bool {
+        self.retry_on.contains(&kind)
+    }
+
+    /// The wait after the `failed`th failed attempt, before the next one.
+    fn delay(&self, failed: u32) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(failed.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    /// Calls `attempt` until it succeeds, it fails in a way that isn't retried, or it runs out of attempts.
+    ///
+    /// `classify` tells what kind of failure a result is, if it is one, and how long the server asked to wait before retrying.
+    pub(crate) async fn run<T, F, Fut, C>(&self, mut attempt: F, classify: C) -> T
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = T>,
+        C: Fn(&T) -> Option<(FailureKind, Option<Duration>)>,
+    {
+        let mut failed = 0;
+        loop {
+            let result = attempt().await;
+            failed += 1;
+
+            match classify(&result) {
+                Some((kind, retry_after)) if failed < self.attempts && self.retries(kind) => {
+                    if retry_after.is_some_and(|wait| wait > self.max_backoff) {
+                        return result;
+                    }
+                    let delay = self.delay(failed).max(retry_after.unwrap_or_default());
+                    async_std::task::sleep(delay).await;
+                }
+                _ => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new()
+    }
+}
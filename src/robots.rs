@@ -0,0 +1,134 @@
+//! Reading robots.txt files, for [`Arguments::robots`](crate::Arguments::robots).
+//!
+//! Follows [RFC 9309](https://www.rfc-editor.org/rfc/rfc9309): the group for the crawler's own name is used if there is one,
+//! and the `*` group otherwise, and the longest matching rule decides, with `Allow` winning ties.
+
+/// The rules of one robots.txt file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Robots {
+    groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Group {
+    /// The lowercase names of the crawlers the group applies to.
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Robots {
+    /// Reads a robots.txt file, skipping any lines that can't be understood.
+    pub(crate) fn parse(text: &str) -> Robots {
+        let mut groups: Vec<Group> = Vec::new();
+        // Consecutive user-agent lines start a single group, which lasts until the next user-agent line after a rule.
+        let mut in_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let (key, value) = match line.split_once(':') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        groups.push(Group::default());
+                        in_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agents = false;
+                    // An empty disallow allows everything, which is the same as not having it.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_owned(),
+                        });
+                    }
+                }
+                _ => in_agents = false,
+            }
+        }
+
+        Robots { groups }
+    }
+
+    /// Rules for a host that doesn't have a robots.txt, which allow everything.
+    pub(crate) fn allow_all() -> Robots {
+        Robots::default()
+    }
+
+    /// Whether `user_agent` can fetch `path`, which includes the query if there is one.
+    ///
+    /// Only the name at the start of the user agent is matched, so `ExampleBot/1.0 (+https://example.com)` follows the rules for `examplebot`.
+    pub(crate) fn allows(&self, user_agent: &str, path: &str) -> bool {
+        let name = user_agent
+            .split(|c: char| c == '/' || c.is_whitespace())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let named: Vec<&Group> = self
+            .groups
+            .iter()
+            .filter(|group| group.agents.contains(&name))
+            .collect();
+        let groups = if named.is_empty() {
+            self.groups
+                .iter()
+                .filter(|group| group.agents.iter().any(|agent| agent == "*"))
+                .collect()
+        } else {
+            named
+        };
+
+        groups
+            .iter()
+            .flat_map(|group| &group.rules)
+            .filter(|rule| matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Matches a path against a rule, where `*` matches anything and a `$` at the end matches the end of the path.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match path.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
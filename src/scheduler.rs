@@ -0,0 +1,93 @@
+//! The queue that hands out work to the download workers.
+//!
+//! Every worker takes its next candidate url from the same queue, so the queue has to stay fast
+//! however many workers there are and however many candidates are waiting.
+//! [`Scheduler`] never locks: each priority is a lock-free queue, so taking the next candidate is `O(1)`
+//! and workers never wait on each other to do it.
+
+use crossbeam_queue::SegQueue;
+use std::fmt;
+
+/// The order work is handed out in. Everything of a higher priority is handed out before anything of a lower one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    High,
+    Normal,
+    Low,
+}
+
+/// A lock-free queue of work, handed out by priority and then in the order it was added.
+///
+/// Candidates are added in rank order, so the best ranked ones are always tried first.
+///
+/// # Example
+/// ```
+/// extern crate image_search;
+///
+/// use image_search::scheduler::{Priority, Scheduler};
+///
+/// let scheduler: Scheduler<&str> = ["first", "second"].into_iter().collect();
+/// scheduler.push("fallback", Priority::Low);
+/// scheduler.push("urgent", Priority::High);
+///
+/// assert_eq!(scheduler.pop(), Some("urgent"));
+/// assert_eq!(scheduler.pop(), Some("first"));
+/// assert_eq!(scheduler.pop(), Some("second"));
+/// assert_eq!(scheduler.pop(), Some("fallback"));
+/// assert_eq!(scheduler.pop(), None);
+/// ```
+pub struct Scheduler<T> {
+    queues: [SegQueue<T>; 3],
+}
+
+impl<T> Scheduler<T> {
+    pub fn new() -> Scheduler<T> {
+        Scheduler {
+            queues: [SegQueue::new(), SegQueue::new(), SegQueue::new()],
+        }
+    }
+
+    /// Adds work to be handed out after everything else of the same priority.
+    pub fn push(&self, item: T, priority: Priority) {
+        self.queues[priority as usize].push(item);
+    }
+
+    /// Takes the next piece of work, or returns `None` if there is none left.
+    pub fn pop(&self) -> Option<T> {
+        self.queues.iter().find_map(SegQueue::pop)
+    }
+
+    /// The amount of work left. Other threads can change it at any time.
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(SegQueue::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(SegQueue::is_empty)
+    }
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
+
+/// Adds every item with [`Priority::Normal`], in order.
+impl<T> FromIterator<T> for Scheduler<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let scheduler = Scheduler::new();
+        for item in iter {
+            scheduler.push(item, Priority::Normal);
+        }
+        scheduler
+    }
+}
+
+impl<T> fmt::Debug for Scheduler<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("len", &self.len())
+            .finish()
+    }
+}
@@ -0,0 +1,121 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! The filters are written with the same names as their `Display` implementations, so they stay stable
+//! if variants are reordered or renamed in the code.
+
+use crate::{
+    Arguments, Color, ColorType, Date, FailureKind, Format, ImageType, License, MinSize, Ratio,
+    SafeSearch, Size, Time,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! string_serde {
+    ($($t:ty),+) => {
+        $(impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(de::Error::custom)
+            }
+        })+
+    };
+}
+string_serde!(
+    Color,
+    ColorType,
+    License,
+    ImageType,
+    Time,
+    Date,
+    Ratio,
+    Format,
+    Size,
+    MinSize,
+    SafeSearch,
+    FailureKind
+);
+
+/// The arguments that fields missing from the input are taken from.
+pub(crate) fn defaults() -> Arguments {
+    Arguments::new("", 0)
+}
+
+/// Reads a limit on concurrent requests, which is raised to 1 the same as by its builder.
+pub(crate) fn at_least_one<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    Ok(usize::deserialize(deserializer)?.max(1))
+}
+
+/// Writes the timeout as a number of seconds.
+pub(crate) mod seconds {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        timeout: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        timeout
+            .map(|timeout| timeout.as_secs_f64())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Writes a duration as a number of seconds.
+pub(crate) mod duration {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        duration.as_secs_f64().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Duration::try_from_secs_f64(f64::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Writes the backends by their names.
+/// Only the backends included in this crate can be read back, since there is no way to construct any others.
+pub(crate) mod backends {
+    use super::*;
+    use crate::backend::{self, SearchBackend};
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(
+        backends: &[Arc<dyn SearchBackend>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(backends.iter().map(|backend| backend.name()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Arc<dyn SearchBackend>>, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        if names.is_empty() {
+            return Err(de::Error::invalid_length(0, &"at least one backend"));
+        }
+
+        names
+            .iter()
+            .map(|name| {
+                backend::by_name(name)
+                    .ok_or_else(|| de::Error::unknown_variant(name, &["google", "bing"]))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,250 @@
+use crate::backend::is_google_domain;
+use crate::{
+    ArgumentError, Arguments, Color, ColorType, Date, Error, Format, Google, ImageType, License,
+    MinSize, Ratio, SafeSearch, SearchBackend, SearchResult, Size, Time,
+};
+use surf::Url;
+
+/// Conversion to and from the urls of searches.
+impl Arguments {
+    /// Reads the arguments out of the url of a Google Images search, such as one that was tuned in the browser.
+    ///
+    /// The query, the filters in `tbs`, `hl`, `gl`, `cr`, `safe` and the country domain of Google are read into the matching arguments.
+    /// `udm`, `tbm` and `start` are left out, since they're set when searching.
+    /// Everything else is kept with [`raw_param`](Arguments::raw_param), so it's still sent when searching,
+    /// and is returned as `key=value`, or `tbs=filter` for filters that aren't supported.
+    ///
+    /// The limit of the arguments is 0, and can be changed with [`limit`](Arguments::limit).
+    ///
+    /// # Errors
+    /// This function will return an error if:
+    /// * The url isn't a Google search
+    /// * The filters can't be searched together, like a date range that ends before it starts
+    ///
+    /// # Example
+    /// ```
+    /// extern crate image_search;
+    ///
+    /// use image_search::{Arguments, Color, Time};
+    ///
+    /// let url = "https://www.google.com/search?q=sunset&tbm=isch&tbs=ic:specific,isc:orange,qdr:w&hl=en&ved=0a";
+    /// let (args, unknown) = Arguments::from_url(url).unwrap();
+    ///
+    /// assert_eq!(args.get_query(), "sunset");
+    /// assert!(matches!(args.get_color(), Color::Orange));
+    /// assert!(matches!(args.get_time(), Time::Week));
+    /// assert_eq!(args.get_language(), Some("en"));
+    /// assert_eq!(unknown, ["ved=0a"]);
+    ///
+    /// let (same, _) = Arguments::from_url(&args.to_url().unwrap()).unwrap();
+    /// assert_eq!(same.to_url().unwrap(), args.to_url().unwrap());
+    /// ```
+    pub fn from_url(url: &str) -> SearchResult<(Arguments, Vec<String>)> {
+        let invalid = || Error::Arguments(ArgumentError::Url(url.to_owned()));
+
+        let parsed = Url::parse(url).map_err(|_| invalid())?;
+        let host = parsed.host_str().unwrap_or_default();
+        let domain = ["www.google.", "images.google.", "google."]
+            .iter()
+            .find_map(|prefix| host.strip_prefix(prefix))
+            .filter(|domain| is_google_domain(domain))
+            .ok_or_else(invalid)?;
+        if parsed.path() != "/search" {
+            return Err(invalid());
+        }
+
+        let mut args = Arguments::new("", 0);
+        if domain != "com" {
+            args = args.domain(domain);
+        }
+
+        let mut unknown = Vec::new();
+        for (key, value) in parsed.query_pairs() {
+            let known = match key.as_ref() {
+                "q" => {
+                    args.query = value.to_string();
+                    true
+                }
+                "hl" => {
+                    args = args.language(value.as_ref());
+                    true
+                }
+                "gl" => {
+                    args = args.region(value.as_ref());
+                    true
+                }
+                "cr" => match value.strip_prefix("country") {
+                    Some(country) if country.chars().all(|c| c.is_ascii_alphabetic()) => {
+                        args = args.country(country);
+                        true
+                    }
+                    _ => false,
+                },
+                "safe" => find(&SafeSearch::ALL, SafeSearch::param, &value)
+                    .map(|safe_search| args.safe_search = safe_search)
+                    .is_some(),
+                "tbs" => {
+                    let filters = read_tbs(&mut args, &value)?;
+                    unknown.extend(filters.iter().map(|filter| format!("tbs={}", filter)));
+                    true
+                }
+                "udm" | "tbm" | "start" => true,
+                _ => false,
+            };
+
+            if !known {
+                unknown.push(format!("{}={}", key, value));
+                args.raw_params.push((key.into_owned(), value.into_owned()));
+            }
+        }
+
+        args.validate()?;
+        Ok((args, unknown))
+    }
+
+    /// Builds the url of the first page of results, from the first backend that was selected.
+    ///
+    /// For Google, this is the url [`from_url`](Arguments::from_url) reads, and the same arguments are read back from it.
+    ///
+    /// # Errors
+    /// This function will return an error if the filters can't be searched together, the same way searching would.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate image_search;
+    ///
+    /// use image_search::{Arguments, ImageType};
+    ///
+    /// let args = Arguments::new("cats", 10).image_type(ImageType::Clipart);
+    /// assert_eq!(
+    ///     args.to_url().unwrap(),
+    ///     "https://www.google.com/search?udm=2&q=cats&tbs=itp%3Aclipart"
+    /// );
+    /// ```
+    pub fn to_url(&self) -> SearchResult<String> {
+        self.validate()?;
+
+        Ok(match self.backends.first() {
+            Some(backend) => backend.build_url(self, 0),
+            None => Google.build_url(self, 0),
+        })
+    }
+}
+
+/// Reads the filters out of a `tbs` value into `args`.
+/// The filters that aren't supported are kept as a raw `tbs` value and returned.
+fn read_tbs(args: &mut Arguments, tbs: &str) -> SearchResult<Vec<String>> {
+    let filters: Vec<(&str, &str)> = tbs
+        .split(',')
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty())
+        .map(|filter| (filter, filter.split(':').next().unwrap_or(filter)))
+        .collect();
+
+    let value = |key: &str| {
+        filters
+            .iter()
+            .find(|(_, name)| *name == key)
+            .and_then(|(filter, _)| filter.split_once(':'))
+            .map(|(_, value)| value)
+    };
+
+    // Date ranges and custom sizes are spread over several filters, so they're read up front.
+    let range = match (
+        value("cdr"),
+        value("cd_min").and_then(date),
+        value("cd_max").and_then(date),
+    ) {
+        (Some("1"), Some(from), Some(to)) => Some(Time::range(from, to)?),
+        _ => None,
+    };
+    let size = match value("isz") {
+        Some("lt") => value("islt")
+            .and_then(|min| find(&MinSize::ALL, MinSize::param, min))
+            .map(Size::LargerThan),
+        Some("ex") => match (
+            value("iszw").and_then(|width| width.parse().ok()),
+            value("iszh").and_then(|height| height.parse().ok()),
+        ) {
+            (Some(width), Some(height)) => Some(Size::Exact { width, height }),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let mut unknown = Vec::new();
+    for &(filter, key) in &filters {
+        let known = match key {
+            "ic" if filter == "ic:specific" => true,
+            "isc" => find(&Color::ALL, Color::param, filter)
+                .map(|color| args.color = color)
+                .is_some(),
+            "ic" => find(&ColorType::ALL, ColorType::param, filter)
+                .map(|color_type| args.color_type = color_type)
+                .is_some(),
+            "il" => find(&License::ALL, License::param, filter)
+                .map(|license| args.license = license)
+                .is_some(),
+            "itp" => find(&ImageType::ALL, ImageType::param, filter)
+                .map(|image_type| args.image_type = image_type)
+                .is_some(),
+            "qdr" => find(
+                &[Time::Day, Time::Week, Time::Month, Time::Year],
+                Time::param,
+                filter,
+            )
+            .map(|time| args.time = time)
+            .is_some(),
+            "cdr" | "cd_min" | "cd_max" => range.map(|time| args.time = time).is_some(),
+            "iar" => find(&Ratio::ALL, Ratio::param, filter)
+                .map(|ratio| args.ratio = ratio)
+                .is_some(),
+            "ift" => find(&Format::ALL, Format::param, filter)
+                .map(|format| args.format = format)
+                .is_some(),
+            "isz" => size
+                .or_else(|| {
+                    find(
+                        &[Size::Large, Size::Medium, Size::Icon],
+                        Size::param,
+                        filter,
+                    )
+                })
+                .map(|size| args.size = size)
+                .is_some(),
+            "islt" => matches!(size, Some(Size::LargerThan(_))),
+            "iszw" | "iszh" => matches!(size, Some(Size::Exact { .. })),
+            _ => false,
+        };
+
+        if !known {
+            unknown.push(filter.to_owned());
+        }
+    }
+
+    if !unknown.is_empty() {
+        args.raw_params
+            .push((String::from("tbs"), unknown.join(",")));
+    }
+
+    Ok(unknown)
+}
+
+/// Finds the variant whose parameter is `param`.
+fn find<T: Copy, P: AsRef<str>>(variants: &[T], to_param: fn(&T) -> P, param: &str) -> Option<T> {
+    variants
+        .iter()
+        .copied()
+        .find(|variant| to_param(variant).as_ref() == param)
+}
+
+/// Reads a date in the `M/D/YYYY` format Google uses.
+fn date(value: &str) -> Option<Date> {
+    let mut parts = value.split('/').map(|part| part.trim().parse::<u16>().ok());
+    let (month, day, year) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Date::new(year, u8::try_from(month).ok()?, u8::try_from(day).ok()?)
+}
@@ -0,0 +1,102 @@
+//! Tests for downloading batches of queries, from a local server where they need one.
+
+extern crate async_std;
+extern crate image_search;
+
+mod common;
+
+use common::{Local, Reply, Server};
+use image_search::{download_batch, Arguments, Batch, Color, ColorType, Error, Politeness, Time};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[test]
+fn reports_every_query_in_order() {
+    let batch = Batch::new(vec![
+        Arguments::new("cats", 5)
+            .color(Color::Red)
+            .color_type(ColorType::Grayscale),
+        Arguments::new("dogs", 5)
+            .raw_param("tbs", "qdr:d")
+            .time(Time::Week),
+    ])
+    .concurrency(1)
+    .rate_limit(10);
+
+    let reports = async_std::task::block_on(download_batch(batch));
+
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].query, "cats");
+    assert_eq!(reports[1].query, "dogs");
+    assert!(reports
+        .iter()
+        .all(|report| matches!(report.result, Err(Error::Arguments(_)))));
+}
+
+#[test]
+fn empty_batches_finish() {
+    assert!(async_std::task::block_on(download_batch(Vec::new())).is_empty());
+}
+
+#[test]
+fn a_failing_query_doesnt_stop_the_others() {
+    let server = Server::start(20, Duration::ZERO, |_| Reply::Image);
+    let batch = Batch::new(vec![
+        Arguments::new("cats", 3)
+            .backend(Local(server.addr))
+            .directory(common::directory("batch_cats")),
+        Arguments::new("dogs", 3)
+            .backend(Local(server.addr))
+            .color(Color::Red)
+            .color_type(ColorType::Grayscale),
+        Arguments::new("birds", 3)
+            .backend(Local(server.addr))
+            .directory(common::directory("batch_birds")),
+    ])
+    .politeness(Politeness::unlimited());
+
+    let reports = async_std::task::block_on(download_batch(batch));
+
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].result.as_ref().unwrap().paths().len(), 3);
+    assert!(matches!(reports[1].result, Err(Error::Arguments(_))));
+    assert_eq!(reports[2].result.as_ref().unwrap().paths().len(), 3);
+}
+
+/// Three queries for the same images, which are each allowed to download all of them at once.
+fn queries(server: &Server, name: &str) -> Vec<Arguments> {
+    (0..3)
+        .map(|i| {
+            Arguments::new("cats", 4)
+                .backend(Local(server.addr))
+                .directory(common::directory(&format!("{}_{}", name, i)))
+                .concurrency(8)
+                .host_concurrency(8)
+        })
+        .collect()
+}
+
+#[test]
+fn limits_apply_across_the_batch() {
+    let server = Server::start(10, Duration::from_millis(50), |_| Reply::Image);
+    let batch = Batch::new(queries(&server, "batch_concurrency"))
+        .concurrency(2)
+        .politeness(Politeness::unlimited());
+
+    let reports = async_std::task::block_on(download_batch(batch));
+    assert!(reports
+        .iter()
+        .all(|report| report.result.as_ref().unwrap().paths().len() == 4));
+    assert_eq!(server.max_active.load(Ordering::SeqCst), 2);
+
+    let server = Server::start(10, Duration::from_millis(50), |_| Reply::Image);
+    let batch = Batch::new(queries(&server, "batch_host_concurrency"))
+        .host_concurrency(1)
+        .politeness(Politeness::unlimited());
+
+    let reports = async_std::task::block_on(download_batch(batch));
+    assert!(reports
+        .iter()
+        .all(|report| report.result.as_ref().unwrap().paths().len() == 4));
+    assert_eq!(server.max_active.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,137 @@
+//! Tests for the command-line tool, which needs the `cli` feature.
+#![cfg(feature = "cli")]
+
+extern crate image_search;
+extern crate serde_json;
+
+use image_search::{Arguments, Color, SafeSearch, Size};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+/// Runs the tool with `line`, after the name of the tool.
+fn run(line: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_image_search"))
+        .args(line)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+/// The arguments a command line describes, as printed by the `arguments` subcommand.
+fn arguments(line: &[&str]) -> Arguments {
+    let output = run(&[&["arguments"], line].concat());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn maps_flags_to_arguments() {
+    let args = arguments(&[
+        "mountain lake",
+        "-n",
+        "5",
+        "--timeout",
+        "2.5",
+        "--crawl-delay",
+        "0.25",
+        "--host-rate",
+        "0",
+        "--attempts",
+        "1",
+        "--color",
+        "red",
+        "--size",
+        "1920x1080",
+        "--domain",
+        "co.uk",
+        "--safe-search",
+        "strict",
+        "--raw",
+        "tbs=itp:photo",
+        "-b",
+        "bing",
+        "-b",
+        "google",
+    ]);
+
+    assert_eq!(args.get_query(), "mountain lake");
+    assert_eq!(args.get_limit(), 5);
+    assert_eq!(args.get_timeout(), Some(Duration::from_millis(2500)));
+    assert_eq!(
+        args.get_politeness().get_crawl_delay(),
+        Duration::from_millis(250)
+    );
+    assert_eq!(args.get_politeness().get_rate_limit(), None);
+    assert_eq!(args.get_retry().get_attempts(), 1);
+    assert!(matches!(args.get_color(), Color::Red));
+    assert!(matches!(
+        args.get_size(),
+        Size::Exact {
+            width: 1920,
+            height: 1080
+        }
+    ));
+    assert_eq!(args.get_domain(), Some("co.uk"));
+    assert!(matches!(args.get_safe_search(), SafeSearch::Strict));
+    assert_eq!(
+        args.get_raw_params(),
+        [(String::from("tbs"), String::from("itp:photo"))]
+    );
+    let backends: Vec<&str> = args.get_backends().iter().map(|b| b.name()).collect();
+    assert_eq!(backends, ["bing", "google"]);
+
+    let args = arguments(&["cats", "--timeout", "0"]);
+    assert_eq!(args.get_timeout(), None);
+
+    let args = arguments(&["--url", "https://www.google.de/search?q=sunset"]);
+    assert_eq!(args.get_query(), "sunset");
+    assert_eq!(args.get_domain(), Some("de"));
+}
+
+#[test]
+fn rejects_durations_that_dont_fit() {
+    for secs in ["1e20", "NaN", "inf", "-1", "soon"] {
+        for flag in ["--timeout", "--crawl-delay"] {
+            let output = run(&["search", "cats", &format!("{}={}", flag, secs)]);
+            assert_eq!(output.status.code(), Some(2), "{} {}", flag, secs);
+            assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value"));
+        }
+    }
+}
+
+#[test]
+fn lists_the_exit_codes() {
+    let help = String::from_utf8(run(&["--help"]).stdout).unwrap();
+    for code in [
+        "  0  Success",
+        "  2  Invalid command line",
+        "  3  Invalid arguments",
+        "  4  Network error",
+        "  5  The results could not be parsed",
+        "  6  Blocked by a consent page",
+        "  7  Blocked by a CAPTCHA",
+        "  8  Rate limited",
+        "  9  Unable to create or write to the directory",
+    ] {
+        assert!(help.contains(code), "{}", code);
+    }
+}
+
+#[test]
+fn exits_with_3_for_invalid_arguments() {
+    let lines: [&[&str]; 2] = [
+        &["urls", "cats", "--domain", "evil.com/"],
+        &[
+            "arguments",
+            "--url",
+            "https://www.bing.com/images/search?q=cats",
+        ],
+    ];
+    for line in lines {
+        assert_eq!(run(line).status.code(), Some(3), "{:?}", line);
+    }
+}
@@ -0,0 +1,219 @@
+//! A local image host and search backend, so downloads can be tested without the network.
+
+#![allow(dead_code)]
+
+use image_search::{Image, ParseStage, Parsed, SearchBackend, Strategy};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The smallest file `infer` recognizes as a png.
+pub const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0";
+
+/// The most images listed on one page of results.
+pub const PAGE: usize = 10;
+
+/// What the server sends back for an image path, after `delay`.
+/// A status or text sent back for `/search` replaces the page of results.
+#[derive(Debug, Clone)]
+pub enum Reply {
+    Image,
+    Status(u16),
+    Text(&'static str),
+    /// A 302 to the url.
+    Redirect(&'static str),
+    /// A 429, with a `Retry-After` header if there are seconds to wait.
+    RateLimited(Option<u64>),
+}
+
+impl Reply {
+    /// The status, extra headers and body of a reply that isn't a page of results or an image.
+    fn response(self) -> (u16, String, Vec<u8>) {
+        match self {
+            Reply::Image => (200, String::new(), PNG.to_vec()),
+            Reply::Status(status) => (status, String::new(), b"not found".to_vec()),
+            Reply::Text(text) => (200, String::new(), text.as_bytes().to_vec()),
+            Reply::Redirect(url) => (302, format!("Location: {}\r\n", url), Vec::new()),
+            Reply::RateLimited(seconds) => {
+                let header = seconds
+                    .map(|seconds| format!("Retry-After: {}\r\n", seconds))
+                    .unwrap_or_default();
+                (429, header, Vec::new())
+            }
+        }
+    }
+}
+
+/// Serves pages of results at `/search?offset=N`, listing up to [`PAGE`] of `/img/0` up to `/img/{images - 1}` from `N` on,
+/// and the images themselves.
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub addr: SocketAddr,
+    /// The requests that were made, by path.
+    pub requests: Arc<Mutex<Vec<String>>>,
+    /// The most image requests that were being answered at once.
+    pub max_active: Arc<AtomicUsize>,
+}
+
+impl Server {
+    pub fn start(images: usize, delay: Duration, reply: fn(&str) -> Reply) -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Server {
+            addr: listener.local_addr().unwrap(),
+            requests: Arc::default(),
+            max_active: Arc::default(),
+        };
+        let active = Arc::new(AtomicUsize::new(0));
+
+        let state = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (state, active) = (state.clone(), active.clone());
+                thread::spawn(move || state.answer(stream, images, delay, reply, &active));
+            }
+        });
+
+        server
+    }
+
+    fn answer(
+        &self,
+        mut stream: TcpStream,
+        images: usize,
+        delay: Duration,
+        reply: fn(&str) -> Reply,
+        active: &AtomicUsize,
+    ) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let path = line.split_whitespace().nth(1).unwrap_or("/").to_owned();
+        while reader.read_line(&mut line).unwrap_or(0) > 2 {
+            line.clear();
+        }
+        self.requests.lock().unwrap().push(path.clone());
+
+        let (status, headers, body) = if path.starts_with("/search") {
+            match reply(&path) {
+                Reply::Status(status) => (status, String::new(), Vec::new()),
+                Reply::Image => {
+                    let offset: usize = path
+                        .split_once("offset=")
+                        .and_then(|(_, offset)| offset.parse().ok())
+                        .unwrap_or(0);
+                    let list = (offset..images.min(offset + PAGE))
+                        .map(|i| format!("http://{}/img/{}", self.addr, i))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    (200, String::new(), list.into_bytes())
+                }
+                other => other.response(),
+            }
+        } else if path == "/robots.txt" {
+            match reply(&path) {
+                Reply::Image => (404, String::new(), Vec::new()),
+                other => other.response(),
+            }
+        } else {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_active.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(delay);
+            active.fetch_sub(1, Ordering::SeqCst);
+
+            reply(&path).response()
+        };
+
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {} OK\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+            status,
+            body.len(),
+            headers
+        );
+        let _ = stream.write_all(&body);
+    }
+
+    /// The number of requests made for `path`.
+    pub fn count(&self, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| *request == path)
+            .count()
+    }
+}
+
+/// Searches a [`Server`], which lists one image url per line. Lines that aren't urls are skipped.
+#[derive(Debug)]
+pub struct Local(pub SocketAddr);
+
+impl SearchBackend for Local {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn build_url(&self, _: &image_search::Arguments, offset: usize) -> String {
+        format!("http://{}/search?offset={}", self.0, offset)
+    }
+
+    fn parse(&self, body: &str) -> Result<Parsed, ParseStage> {
+        let (urls, skipped): (Vec<&str>, Vec<&str>) =
+            body.lines().partition(|line| line.starts_with("http"));
+        let images: Vec<Image> = urls
+            .into_iter()
+            .map(|url| Image {
+                url: url.to_owned(),
+                width: 1,
+                height: 1,
+                thumbnail: url.to_owned(),
+                source: String::new(),
+            })
+            .collect();
+
+        if images.is_empty() {
+            return Err(ParseStage::NoEntries);
+        }
+        Ok(Parsed {
+            images,
+            skipped: skipped.len(),
+            strategy: Strategy::Markup,
+        })
+    }
+}
+
+/// Searches a [`Server`] like [`Local`], but lists every page backwards and spells the urls differently,
+/// as if another search engine had found the same images.
+#[derive(Debug)]
+pub struct Mirror(pub SocketAddr);
+
+impl SearchBackend for Mirror {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn build_url(&self, args: &image_search::Arguments, offset: usize) -> String {
+        Local(self.0).build_url(args, offset)
+    }
+
+    fn parse(&self, body: &str) -> Result<Parsed, ParseStage> {
+        let mut parsed = Local(self.0).parse(body)?;
+        parsed.images.reverse();
+        for image in &mut parsed.images {
+            let url = image.url.replace("http://", "HTTPS://www.") + "/#mirror";
+            image.thumbnail = url.clone();
+            image.url = url;
+        }
+        Ok(parsed)
+    }
+}
+
+/// An empty directory to download to, unique to `name`.
+pub fn directory(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("image_search_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
@@ -0,0 +1,343 @@
+//! Downloads from a local server, to check the limits put on downloading.
+
+extern crate async_std;
+extern crate image_search;
+
+mod common;
+
+use common::{Local, Reply, Server};
+use image_search::{
+    Arguments, DownloadError, Error, FailureKind, Politeness, RetryPolicy, SkipReason,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[test]
+fn downloads_up_to_the_limit() {
+    let server = Server::start(20, Duration::ZERO, |_| Reply::Image);
+    let dir = common::directory("limit");
+
+    let args = Arguments::new("cats", 5)
+        .backend(Local(server.addr))
+        .directory(&dir);
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+
+    assert_eq!(paths.len(), 5);
+    assert!(paths
+        .iter()
+        .all(|path| path.extension().unwrap() == "png" && path.starts_with(&dir)));
+}
+
+#[test]
+fn limits_concurrent_downloads() {
+    let server = Server::start(20, Duration::from_millis(50), |_| Reply::Image);
+
+    let args = Arguments::new("cats", 12)
+        .backend(Local(server.addr))
+        .directory(common::directory("host_concurrency"))
+        .concurrency(6)
+        .host_concurrency(2)
+        .politeness(Politeness::unlimited());
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+
+    assert_eq!(paths.len(), 12);
+    assert_eq!(server.max_active.load(Ordering::SeqCst), 2);
+
+    let server = Server::start(20, Duration::from_millis(50), |_| Reply::Image);
+    let args = Arguments::new("cats", 12)
+        .backend(Local(server.addr))
+        .directory(common::directory("concurrency"))
+        .concurrency(1)
+        .politeness(Politeness::unlimited());
+    async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+    assert_eq!(server.max_active.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn spaces_out_requests_to_the_same_host() {
+    let server = Server::start(10, Duration::ZERO, |_| Reply::Image);
+    let args = Arguments::new("cats", 4)
+        .backend(Local(server.addr))
+        .directory(common::directory("crawl_delay"))
+        .politeness(Politeness::unlimited().crawl_delay(Duration::from_millis(100)));
+
+    let start = Instant::now();
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+    assert_eq!(paths.len(), 4);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+
+    let server = Server::start(10, Duration::ZERO, |_| Reply::Image);
+    let args = Arguments::new("cats", 6)
+        .backend(Local(server.addr))
+        .directory(common::directory("host_rate"))
+        .politeness(Politeness::unlimited().rate_limit(10.0).burst(2));
+
+    let start = Instant::now();
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+    assert_eq!(paths.len(), 6);
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
+
+#[test]
+fn falls_back_on_the_next_image() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/img/0" | "/img/2" => Reply::Status(404),
+        _ => Reply::Image,
+    });
+
+    let args = Arguments::new("cats", 4)
+        .backend(Local(server.addr))
+        .directory(common::directory("fallback"))
+        .concurrency(1);
+    let report = async_std::task::block_on(image_search::download(args)).unwrap();
+
+    assert_eq!(report.paths().len(), 4);
+    assert_eq!(server.count("/img/0"), 1);
+    assert_eq!(server.count("/img/6"), 0);
+
+    // Every image that was tried is reported in rank order, the failed ones included.
+    assert_eq!(report.images.len(), 6);
+    assert!(report.images[0].image.url.ends_with("/img/0"));
+    assert!(matches!(
+        report.images[0].result,
+        Err(DownloadError::Status { status: 404, .. })
+    ));
+    assert_eq!(report.images[0].attempts, 1);
+    assert_eq!(report.images[1].bytes, common::PNG.len() as u64);
+    assert_eq!(report.failures().count(), 2);
+}
+
+#[test]
+fn retries_before_falling_back() {
+    static FAILED: AtomicUsize = AtomicUsize::new(0);
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/img/0" if FAILED.fetch_add(1, Ordering::SeqCst) < 2 => Reply::Status(503),
+        "/img/1" => Reply::Status(500),
+        _ => Reply::Image,
+    });
+
+    let args = Arguments::new("cats", 2)
+        .backend(Local(server.addr))
+        .directory(common::directory("retry"))
+        .concurrency(1)
+        .retry(RetryPolicy::new().backoff(Duration::from_millis(10), Duration::from_millis(20)));
+    let report = async_std::task::block_on(image_search::download(args)).unwrap();
+
+    assert_eq!(report.paths().len(), 2);
+    assert_eq!(server.count("/img/0"), 3);
+    assert_eq!(server.count("/img/1"), 3);
+    assert_eq!(server.count("/img/2"), 1);
+
+    assert_eq!(report.images[0].attempts, 3);
+    let failed = &report.images[1];
+    assert_eq!(failed.attempts, 3);
+    assert_eq!(
+        failed.result.as_ref().unwrap_err().kind(),
+        Some(FailureKind::ServerError)
+    );
+}
+
+#[test]
+fn retries_only_the_kinds_asked_for() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/img/0" => Reply::Status(503),
+        "/img/1" => Reply::Status(404),
+        _ => Reply::Image,
+    });
+
+    let retry = RetryPolicy::new()
+        .backoff(Duration::from_millis(10), Duration::from_millis(20))
+        .retry_on([FailureKind::ClientError]);
+    let args = Arguments::new("cats", 1)
+        .backend(Local(server.addr))
+        .directory(common::directory("retry_on"))
+        .concurrency(1)
+        .retry(retry);
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(server.count("/img/0"), 1);
+    assert_eq!(server.count("/img/1"), 3);
+}
+
+#[test]
+fn retries_the_search() {
+    static FAILED: AtomicUsize = AtomicUsize::new(0);
+    let server = Server::start(10, Duration::ZERO, |path| {
+        if path.starts_with("/search") && FAILED.fetch_add(1, Ordering::SeqCst) == 0 {
+            Reply::Status(503)
+        } else {
+            Reply::Image
+        }
+    });
+
+    let args = Arguments::new("cats", 3)
+        .backend(Local(server.addr))
+        .retry(RetryPolicy::new().backoff(Duration::from_millis(10), Duration::from_millis(20)));
+    let images = async_std::task::block_on(image_search::search(args.clone())).unwrap();
+    assert_eq!(images.len(), 3);
+
+    FAILED.store(0, Ordering::SeqCst);
+    let err = async_std::task::block_on(image_search::search(args.retry(RetryPolicy::never())));
+    assert!(err.is_err());
+}
+
+#[test]
+fn waits_as_long_as_the_server_asks_up_to_the_max() {
+    static LIMITED: AtomicUsize = AtomicUsize::new(0);
+    let server = Server::start(10, Duration::ZERO, |path| {
+        if path.starts_with("/search") && LIMITED.fetch_add(1, Ordering::SeqCst) == 0 {
+            Reply::RateLimited(Some(1))
+        } else {
+            Reply::Image
+        }
+    });
+
+    let args = Arguments::new("cats", 3)
+        .backend(Local(server.addr))
+        .retry(RetryPolicy::new().backoff(Duration::from_millis(10), Duration::from_secs(2)));
+    let start = Instant::now();
+    let images = async_std::task::block_on(image_search::search(args)).unwrap();
+    assert_eq!(images.len(), 3);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/search?offset=0" => Reply::RateLimited(Some(3600)),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 3).backend(Local(server.addr));
+    let start = Instant::now();
+    let err = async_std::task::block_on(image_search::search(args)).unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::RateLimited {
+            retry_after: Some(wait)
+        } if wait == Duration::from_secs(3600)
+    ));
+    assert_eq!(server.count("/search?offset=0"), 1);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn follows_robots_txt() {
+    const ROBOTS: &str = "\
+User-agent: *
+Disallow: /
+
+# Images ending in 1 are off limits
+User-agent: DatasetBot
+User-agent: OtherBot
+Disallow: /img/*1$
+Allow: /img/
+";
+    let robots = |path: &str| match path {
+        "/robots.txt" => Reply::Text(ROBOTS),
+        _ => Reply::Image,
+    };
+
+    let server = Server::start(20, Duration::ZERO, robots);
+    let args = Arguments::new("cats", 12)
+        .backend(Local(server.addr))
+        .directory(common::directory("robots"))
+        .politeness(Politeness::unlimited())
+        .robots("DatasetBot/1.0 (+https://example.com/bot)");
+    let report = async_std::task::block_on(image_search::download(args)).unwrap();
+
+    assert_eq!(report.paths().len(), 12);
+    assert_eq!(server.count("/robots.txt"), 1);
+    let skipped = report.failures().next().unwrap();
+    assert!(skipped.image.url.ends_with("/img/1"));
+    assert!(matches!(
+        skipped.result,
+        Err(DownloadError::Skipped(SkipReason::RobotsDisallowed))
+    ));
+    assert_eq!(skipped.attempts, 0);
+    assert_eq!(server.count("/img/1"), 0);
+    assert_eq!(server.count("/img/11"), 0);
+    assert_eq!(server.count("/img/10"), 1);
+
+    let server = Server::start(20, Duration::ZERO, robots);
+    let args = Arguments::new("cats", 5)
+        .backend(Local(server.addr))
+        .directory(common::directory("robots_everyone"))
+        .robots("SomeBot");
+    let paths = async_std::task::block_on(image_search::download(args))
+        .unwrap()
+        .into_paths();
+
+    assert!(paths.is_empty());
+    assert_eq!(server.count("/robots.txt"), 1);
+    assert_eq!(server.count("/img/0"), 0);
+}
+
+#[test]
+fn reports_why_robots_txt_couldnt_be_fetched() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/robots.txt" => Reply::Status(503),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 2)
+        .backend(Local(server.addr))
+        .directory(common::directory("robots_unreachable"))
+        .retry(RetryPolicy::never())
+        .robots("DatasetBot");
+    let report = async_std::task::block_on(image_search::download(args)).unwrap();
+
+    assert!(report.paths().is_empty());
+    assert!(report.images.iter().all(|image| matches!(
+        image.result,
+        Err(DownloadError::Status { status: 503, .. })
+    ) && image.attempts == 0));
+    assert_eq!(server.count("/img/0"), 0);
+}
+
+#[test]
+fn reports_invalid_urls() {
+    let server = Server::start(0, Duration::ZERO, |path| match path {
+        "/search?offset=0" => Reply::Text("http://bad host/0\nhttp://bad host/1\n"),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 1)
+        .backend(Local(server.addr))
+        .directory(common::directory("invalid_url"));
+
+    for args in [args.clone(), args.robots("DatasetBot")] {
+        let report = async_std::task::block_on(image_search::download(args)).unwrap();
+
+        assert_eq!(report.images.len(), 2);
+        assert!(report.images.iter().all(|image| matches!(
+            &image.result,
+            Err(DownloadError::InvalidUrl(url)) if *url == image.image.url
+        )));
+    }
+}
+
+#[test]
+fn robots_txt_is_opt_in() {
+    let server = Server::start(10, Duration::ZERO, |_| Reply::Image);
+    let args = Arguments::new("cats", 2)
+        .backend(Local(server.addr))
+        .directory(common::directory("no_robots"));
+    async_std::task::block_on(image_search::download(args.clone())).unwrap();
+    assert_eq!(server.count("/robots.txt"), 0);
+
+    // Without a robots.txt everything is allowed.
+    let paths = async_std::task::block_on(image_search::download(args.robots("DatasetBot")))
+        .unwrap()
+        .into_paths();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(server.count("/robots.txt"), 1);
+}
@@ -0,0 +1,161 @@
+//! Tests for loading job files, which need the `jobs` feature.
+#![cfg(feature = "jobs")]
+
+extern crate image_search;
+
+use image_search::jobs::{self, JobError};
+use image_search::{Color, Size, Time};
+use std::time::Duration;
+
+const JOB: &str = r#"
+[defaults]
+limit = 20
+timeout = 2.5
+color = "red"
+raw_params = { tbs = "sur:fmc" }
+
+[[queries]]
+query = "cats"
+
+[[queries]]
+query = "dogs"
+limit = 5
+color = "none"
+size = "larger_than_4mp"
+time = "2024-01-01..2024-06-30"
+"#;
+
+fn invalid(err: JobError) -> (Option<usize>, Option<usize>, String) {
+    match err {
+        JobError::Invalid {
+            line, column, key, ..
+        } => (line, column, key),
+        err => panic!("expected an invalid job, got {:?}", err),
+    }
+}
+
+#[test]
+fn queries_override_defaults() {
+    let args = jobs::from_toml(JOB).unwrap();
+
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[0].get_query(), "cats");
+    assert_eq!(args[0].get_limit(), 20);
+    assert!(matches!(args[0].get_color(), Color::Red));
+    assert_eq!(
+        args[0].get_raw_params(),
+        [("tbs".to_owned(), "sur:fmc".to_owned())]
+    );
+
+    assert_eq!(args[1].get_limit(), 5);
+    assert!(matches!(args[1].get_color(), Color::None));
+    assert!(matches!(args[1].get_size(), Size::LargerThan(_)));
+    assert!(matches!(args[1].get_time(), Time::Range { .. }));
+    assert_eq!(
+        args[1].to_url().unwrap(),
+        "https://www.google.com/search?udm=2&q=dogs&tbs=cdr%3A1%2Ccd_min%3A1%2F1%2F2024%2Ccd_max%3A6%2F30%2F2024%2Cisz%3Alt%2Cislt%3A4mp%2Csur%3Afmc"
+    );
+}
+
+#[test]
+fn queries_can_be_plain_text() {
+    let args =
+        jobs::from_json(r#"{"defaults": {"limit": 3}, "queries": ["cats", "dogs"]}"#).unwrap();
+
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[1].get_query(), "dogs");
+    assert_eq!(args[1].get_limit(), 3);
+}
+
+#[test]
+fn errors_point_at_the_key() {
+    let job = JOB.replace(r#"size = "larger_than_4mp""#, r#"size = "huge""#);
+    assert_eq!(
+        invalid(jobs::from_toml(&job).unwrap_err()),
+        (Some(15), Some(8), String::from("queries[1].size"))
+    );
+
+    let job = JOB.replace("limit = 5", "limt = 5");
+    let (line, _, key) = invalid(jobs::from_toml(&job).unwrap_err());
+    assert_eq!(line, Some(13));
+    assert_eq!(key, "queries[1].limt");
+
+    let (line, _, key) = invalid(
+        jobs::from_json(
+            "{\"queries\": [\n  {\"query\": \"cats\", \"backends\": [\"altavista\"]}\n]}",
+        )
+        .unwrap_err(),
+    );
+    assert_eq!(line, Some(2));
+    assert_eq!(key, "queries[0].backends");
+}
+
+#[test]
+fn rejects_invalid_queries() {
+    let (line, _, key) = invalid(jobs::from_toml("[[queries]]\nlimit = 5").unwrap_err());
+    assert_eq!(line, None);
+    assert_eq!(key, "queries[0].query");
+
+    let err = jobs::from_json(
+        r#"{"queries": [{"query": "cats", "color": "red", "color_type": "grayscale"}]}"#,
+    )
+    .unwrap_err();
+    assert_eq!(invalid(err).2, "queries[0]");
+
+    assert!(matches!(
+        jobs::load("job.yaml"),
+        Err(JobError::Extension(_))
+    ));
+    assert!(matches!(jobs::load("missing.toml"), Err(JobError::Io(_))));
+}
+
+#[test]
+fn checks_politeness_and_retry_tables() {
+    let args = jobs::from_toml(
+        r#"
+[defaults]
+politeness = { rate_limit = 2, burst = 3 }
+retry = { attempts = 5, max_backoff = 60 }
+
+[[queries]]
+query = "cats"
+politeness = { rate_limit = 0 }
+retry = { backoff = 10 }
+"#,
+    )
+    .unwrap();
+    let politeness = args[0].get_politeness();
+    assert_eq!(politeness.get_rate_limit(), None);
+    assert_eq!(politeness.get_burst(), 3);
+    assert_eq!(args[0].get_retry().get_attempts(), 5);
+    assert_eq!(
+        args[0].get_retry().get_backoff(),
+        (Duration::from_secs(10), Duration::from_secs(60))
+    );
+
+    let rejected = [
+        (
+            "[[queries]]\nquery = \"cats\"\nretry = { backoff = 5, max_backoff = 1 }",
+            "queries[0].retry.max_backoff",
+        ),
+        (
+            "[defaults]\nretry = { max_backoff = 1 }\n[[queries]]\nquery = \"cats\"\nretry = { backoff = 5 }",
+            "queries[0].retry.max_backoff",
+        ),
+        (
+            "[[queries]]\nquery = \"cats\"\nretry = { attempts = 0 }",
+            "queries[0].retry.attempts",
+        ),
+        (
+            "[defaults]\npoliteness = { rate_limit = -1 }\n[[queries]]\nquery = \"cats\"",
+            "defaults.politeness.rate_limit",
+        ),
+        (
+            "[[queries]]\nquery = \"cats\"\npoliteness = { burst = 0 }",
+            "queries[0].politeness.burst",
+        ),
+    ];
+    for (job, key) in rejected {
+        assert_eq!(invalid(jobs::from_toml(job).unwrap_err()).2, key, "{}", job);
+    }
+}
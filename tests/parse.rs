@@ -0,0 +1,167 @@
+//! Property tests making sure the parsers never panic, whatever the search engine sends back.
+
+extern crate image_search;
+extern crate proptest;
+extern crate serde_json;
+
+use image_search::{Bing, Google, ParseStage, SearchBackend};
+use proptest::prelude::*;
+
+/// A Google image entry, as found in the `var m={...}` script blob.
+fn google_entry(i: usize) -> String {
+    format!(
+        r#"[1,[0,"id{i}",["https://encrypted-tbn0.gstatic.com/images?q={i}",120,90],["https://example.com/{i}.jpg",1200,900],null,null,null,null,null,{{"2003":[null,"id{i}","https://example.com/page/{i}","Title {i}"]}}]]"#
+    )
+}
+
+fn google_page(entries: &[String]) -> String {
+    let blob = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| format!(r#""key{}":{}"#, i, entry))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("<html><script>var m={{{}}};var a=m;</script></html>", blob)
+}
+
+/// Breaks an entry by truncating it before `field`, or by replacing one of the fields that are read with `null`.
+fn break_entry(entry: &str, field: usize, truncate: bool) -> String {
+    let mut json: serde_json::Value = serde_json::from_str(entry).unwrap();
+    let obj = json[1].as_array_mut().unwrap();
+
+    if truncate {
+        obj.truncate(field);
+    } else {
+        obj[[2, 3, 9][field % 3]] = serde_json::Value::Null;
+    }
+
+    json.to_string()
+}
+
+fn bing_page(n: usize) -> String {
+    (0..n)
+        .map(|i| {
+            format!(
+                r#"<a class="iusc" m="{{&quot;murl&quot;:&quot;https://example.com/{i}.jpg&quot;,&quot;turl&quot;:&quot;https://tse.mm.bing.net/th?id={i}&quot;,&quot;purl&quot;:&quot;https://example.com/page/{i}&quot;}}"></a><span class="nowrap">1200 x 900 · jpeg</span>"#
+            )
+        })
+        .collect()
+}
+
+/// Applies a random mutation to `page`: truncation, deletion, duplication or insertion of arbitrary text.
+fn mutate(page: String) -> impl Strategy<Value = String> {
+    let len = page.len();
+    (0..4u8, 0..=len, 0..=len, ".{0,16}").prop_map(move |(kind, a, b, text)| {
+        let boundary = |i: usize| {
+            (0..=i)
+                .rev()
+                .find(|&i| page.is_char_boundary(i))
+                .unwrap_or(0)
+        };
+        let (start, end) = (boundary(a.min(b)), boundary(a.max(b)));
+
+        match kind {
+            0 => page[..start].to_owned(),
+            1 => page[..start].to_owned() + &page[end..],
+            2 => page[..end].to_owned() + &page[start..],
+            _ => page[..start].to_owned() + &text + &page[start..],
+        }
+    })
+}
+
+fn mutated_google_page() -> impl Strategy<Value = String> {
+    (1..20usize)
+        .prop_map(|n| google_page(&(0..n).map(google_entry).collect::<Vec<_>>()))
+        .prop_flat_map(mutate)
+}
+
+proptest! {
+    #[test]
+    fn google_never_panics_on_random_input(body in any::<String>()) {
+        let _ = Google.parse(&body);
+    }
+
+    #[test]
+    fn bing_never_panics_on_random_input(body in any::<String>()) {
+        let _ = Bing.parse(&body);
+    }
+
+    #[test]
+    fn google_never_panics_on_random_markup(body in r#"(<img|<script>|var m=\{|var a=m|;|AF_initDataCallback\(|data:|, sideChannel:|src="|data-src="|"|\[|\]|\{|\}|,|[0-9]{1,3}|https://x/)*"#) {
+        let _ = Google.parse(&body);
+    }
+
+    #[test]
+    fn google_never_panics_on_mutated_pages(body in mutated_google_page()) {
+        let _ = Google.parse(&body);
+    }
+
+    #[test]
+    fn bing_never_panics_on_mutated_pages(body in (1..20usize).prop_map(bing_page).prop_flat_map(mutate)) {
+        let _ = Bing.parse(&body);
+    }
+
+    #[test]
+    fn google_skips_malformed_entries(valid in 1..20usize, broken in proptest::collection::vec((0..10usize, any::<bool>()), 0..10)) {
+        let mut entries: Vec<String> = (0..valid).map(google_entry).collect();
+        entries.extend(broken.iter().map(|&(field, truncate)| break_entry(&google_entry(0), field, truncate)));
+
+        let parsed = Google.parse(&google_page(&entries)).unwrap();
+        prop_assert_eq!(parsed.images.len(), valid);
+        prop_assert_eq!(parsed.skipped, broken.len());
+    }
+
+    #[test]
+    fn bing_skips_malformed_entries(valid in 1..20usize, broken in 0..10usize) {
+        let page = bing_page(valid) + &r#"<a class="iusc" m="{&quot;murl&quot;:1}"></a>"#.repeat(broken);
+
+        let parsed = Bing.parse(&page).unwrap();
+        prop_assert_eq!(parsed.images.len(), valid);
+        prop_assert_eq!(parsed.skipped, broken);
+    }
+}
+
+#[test]
+fn google_parses_every_entry() {
+    let parsed = Google
+        .parse(&google_page(&(0..5).map(google_entry).collect::<Vec<_>>()))
+        .unwrap();
+
+    assert_eq!(parsed.images.len(), 5);
+    assert_eq!(parsed.skipped, 0);
+    assert_eq!(parsed.images[0].url, "https://example.com/0.jpg");
+    assert_eq!(parsed.images[0].source, "https://example.com/page/0");
+}
+
+#[test]
+fn bing_parses_dimensions() {
+    let parsed = Bing.parse(&bing_page(1)).unwrap();
+
+    assert_eq!(parsed.images[0].width, 1200);
+    assert_eq!(parsed.images[0].height, 900);
+}
+
+#[test]
+fn parse_results_reports_strategy() {
+    let parsed =
+        image_search::parse_results(&google_page(&[google_entry(0), "[1,[]]".into()])).unwrap();
+
+    assert_eq!(parsed.images.len(), 1);
+    assert_eq!(parsed.skipped, 1);
+    assert_eq!(parsed.strategy, image_search::Strategy::Script);
+    assert!(image_search::parse_results("<html></html>").is_err());
+}
+
+#[test]
+fn reports_failing_stage() {
+    let stage = |body: &str| Google.parse(body).unwrap_err();
+
+    assert_eq!(stage("<html></html>"), ParseStage::BlobNotFound);
+    assert_eq!(stage("var m={\"a\":1}"), ParseStage::ScriptEndNotFound);
+    assert_eq!(stage("var m={\"a\":};var a=m"), ParseStage::InvalidJson);
+    assert_eq!(
+        stage(&google_page(&["[1,[]]".into()])),
+        ParseStage::NoEntries
+    );
+}
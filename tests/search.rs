@@ -0,0 +1,217 @@
+//! Searches a local server, to check how result pages are requested and combined.
+
+extern crate async_std;
+extern crate futures;
+extern crate image_search;
+
+mod common;
+
+use common::{Local, Mirror, Reply, Server};
+use futures::StreamExt;
+use image_search::{Arguments, Error, PageReport, RetryPolicy, Strategy};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn moves_through_the_pages() {
+    let server = Server::start(30, Duration::ZERO, |_| Reply::Image);
+
+    let args = Arguments::new("cats", 25).backend(Local(server.addr));
+    let images = async_std::task::block_on(image_search::search(args)).unwrap();
+
+    assert_eq!(images.len(), 25);
+    for (i, image) in images.iter().enumerate() {
+        assert!(image.url.ends_with(&format!("/img/{}", i)));
+    }
+    assert_eq!(server.count("/search?offset=0"), 1);
+    assert_eq!(server.count("/search?offset=10"), 1);
+    assert_eq!(server.count("/search?offset=20"), 1);
+    assert_eq!(server.count("/search?offset=30"), 0);
+}
+
+#[test]
+fn streams_up_to_the_limit() {
+    let server = Server::start(30, Duration::ZERO, |_| Reply::Image);
+
+    let args = Arguments::new("cats", 15).backend(Local(server.addr));
+    let images: Vec<_> =
+        async_std::task::block_on(image_search::search_stream(args).collect::<Vec<_>>());
+
+    assert_eq!(images.len(), 15);
+    assert!(images.iter().all(Result::is_ok));
+    // The stream stops requesting pages once it has yielded enough images.
+    assert_eq!(server.count("/search?offset=10"), 1);
+    assert_eq!(server.count("/search?offset=20"), 0);
+}
+
+#[test]
+fn a_limit_of_zero_means_every_page() {
+    let server = Server::start(30, Duration::ZERO, |_| Reply::Image);
+    let args = Arguments::new("cats", 0).backend(Local(server.addr));
+
+    let streamed: Vec<_> =
+        async_std::task::block_on(image_search::search_stream(args.clone()).collect::<Vec<_>>());
+    assert_eq!(streamed.len(), 30);
+    assert!(streamed.iter().all(Result::is_ok));
+    assert_eq!(server.count("/search?offset=30"), 1);
+
+    let searched = async_std::task::block_on(image_search::search(args)).unwrap();
+    assert_eq!(searched.len(), 30);
+    assert_eq!(server.count("/search?offset=30"), 2);
+}
+
+/// The number of the image a url points to, however the backend spelled it.
+fn number(url: &str) -> usize {
+    let (_, rest) = url.split_once("/img/").unwrap();
+    rest.trim_end_matches("/#mirror").parse().unwrap()
+}
+
+#[test]
+fn interleaves_backends_without_duplicates() {
+    let server = Server::start(30, Duration::ZERO, |_| Reply::Image);
+    let args = Arguments::new("cats", 10)
+        .backend(Local(server.addr))
+        .add_backend(Mirror(server.addr));
+
+    let images = async_std::task::block_on(image_search::search(args.clone())).unwrap();
+    let numbers: Vec<usize> = images.iter().map(|image| number(&image.url)).collect();
+    assert_eq!(numbers, [0, 9, 1, 8, 2, 7, 3, 6, 4, 5]);
+    assert!(images[1].url.starts_with("HTTPS://www."));
+
+    let streamed: Vec<_> =
+        async_std::task::block_on(image_search::search_stream(args.limit(0)).collect::<Vec<_>>());
+    let mut numbers: Vec<usize> = streamed
+        .into_iter()
+        .map(|image| number(&image.unwrap().url))
+        .collect();
+    numbers.sort_unstable();
+    assert_eq!(numbers, (0..30).collect::<Vec<_>>());
+}
+
+#[test]
+fn reports_every_page() {
+    let server = Server::start(25, Duration::ZERO, |_| Reply::Image);
+    let pages = Arc::new(Mutex::new(Vec::new()));
+    let reported = pages.clone();
+    let args = Arguments::new("cats", 0)
+        .backend(Local(server.addr))
+        .on_page(move |page: &PageReport| reported.lock().unwrap().push(page.clone()));
+
+    async_std::task::block_on(image_search::search_stream(args.clone()).collect::<Vec<_>>());
+    let offsets: Vec<(usize, usize)> = pages
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|page| (page.offset, page.images))
+        .collect();
+    assert_eq!(offsets, [(0, 10), (10, 10), (20, 5)]);
+    assert!(pages
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|page| page.backend == "local" && page.strategy == Strategy::Markup));
+
+    pages.lock().unwrap().clear();
+    let args = args.limit(3).directory(common::directory("on_page"));
+    async_std::task::block_on(image_search::download(args)).unwrap();
+    assert_eq!(pages.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn reports_skipped_results() {
+    let server = Server::start(0, Duration::ZERO, |path| match path {
+        "/search?offset=0" => {
+            Reply::Text("http://example.com/0\nnot a url\nhttp://example.com/1\n")
+        }
+        _ => Reply::Image,
+    });
+    let pages = Arc::new(Mutex::new(Vec::new()));
+    let reported = pages.clone();
+    let args = Arguments::new("cats", 0)
+        .backend(Local(server.addr))
+        .on_page(move |page: &PageReport| reported.lock().unwrap().push(page.clone()));
+
+    let images = async_std::task::block_on(image_search::search(args)).unwrap();
+    assert_eq!(images.len(), 2);
+    let pages = pages.lock().unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].images, 2);
+    assert_eq!(pages[0].skipped, 1);
+}
+
+#[test]
+fn fails_when_a_later_page_is_blocked() {
+    let server = Server::start(30, Duration::ZERO, |path| match path {
+        "/search?offset=10" => Reply::Text("<div class=\"g-recaptcha\"></div>"),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 25).backend(Local(server.addr));
+
+    let err = async_std::task::block_on(image_search::search(args.clone())).unwrap_err();
+    assert!(matches!(err, Error::Captcha));
+
+    let streamed: Vec<_> =
+        async_std::task::block_on(image_search::search_stream(args).collect::<Vec<_>>());
+    assert_eq!(streamed.len(), 11);
+    assert!(streamed[..10].iter().all(Result::is_ok));
+    assert!(matches!(streamed[10], Err(Error::Captcha)));
+    assert_eq!(server.count("/search?offset=20"), 0);
+}
+
+#[test]
+fn recognizes_consent_pages() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/search?offset=0" => {
+            Reply::Redirect("https://consent.google.com/ml?continue=https://www.google.com/search")
+        }
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 5).backend(Local(server.addr));
+
+    let err = async_std::task::block_on(image_search::search(args)).unwrap_err();
+    assert!(matches!(err, Error::Consent));
+}
+
+#[test]
+fn recognizes_captchas() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/search?offset=0" => {
+            Reply::Text("Our systems have detected unusual traffic from your computer network.")
+        }
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 5).backend(Local(server.addr));
+
+    let err = async_std::task::block_on(image_search::search(args)).unwrap_err();
+    assert!(matches!(err, Error::Captcha));
+}
+
+#[test]
+fn recognizes_rate_limits() {
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/search?offset=0" => Reply::RateLimited(Some(120)),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 5)
+        .backend(Local(server.addr))
+        .retry(RetryPolicy::never());
+
+    let err = async_std::task::block_on(image_search::search(args)).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::RateLimited {
+            retry_after: Some(wait)
+        } if wait == Duration::from_secs(120)
+    ));
+
+    let server = Server::start(10, Duration::ZERO, |path| match path {
+        "/search?offset=0" => Reply::RateLimited(None),
+        _ => Reply::Image,
+    });
+    let args = Arguments::new("cats", 5)
+        .backend(Local(server.addr))
+        .retry(RetryPolicy::never());
+
+    let err = async_std::task::block_on(image_search::search(args)).unwrap_err();
+    assert!(matches!(err, Error::RateLimited { retry_after: None }));
+}
@@ -0,0 +1,205 @@
+//! Tests that the filters and arguments survive being written as text and read back.
+
+extern crate image_search;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use image_search::{
+    ArgumentError, Color, ColorType, Date, FailureKind, Format, ImageType, License, MinSize, Ratio,
+    SafeSearch, Size, Time,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+
+fn round_trips<T>(values: &[T])
+where
+    T: Display + FromStr<Err = ArgumentError> + std::fmt::Debug,
+{
+    for value in values {
+        let text = value.to_string();
+        let parsed: T = text.parse().unwrap();
+
+        assert_eq!(parsed.to_string(), text, "{:?}", value);
+        assert_eq!(text, text.to_lowercase());
+    }
+}
+
+#[test]
+fn names_round_trip() {
+    round_trips(&[Color::None, Color::Red, Color::Brown]);
+    round_trips(&[ColorType::Grayscale, ColorType::Transparent]);
+    round_trips(&[License::CreativeCommons, License::Other]);
+    round_trips(&[ImageType::Face, ImageType::Lineart]);
+    round_trips(&[Ratio::Square, Ratio::Panoramic]);
+    round_trips(&[Format::Webp, Format::Raw]);
+    round_trips(&[MinSize::Qsvga, MinSize::Mp70]);
+    round_trips(&[SafeSearch::None, SafeSearch::Blur]);
+    round_trips(&FailureKind::ALL);
+    round_trips(&[
+        Size::Icon,
+        Size::LargerThan(MinSize::Mp4),
+        Size::Exact {
+            width: 1920,
+            height: 1080,
+        },
+    ]);
+
+    let from = Date::new(2024, 2, 29).unwrap();
+    let to = Date::new(2024, 8, 11).unwrap();
+    round_trips(&[Time::Week, Time::range(from, to).unwrap()]);
+}
+
+#[test]
+fn stable_names() {
+    assert_eq!(License::CreativeCommons.to_string(), "creative_commons");
+    assert_eq!(
+        Size::LargerThan(MinSize::Mp2).to_string(),
+        "larger_than_2mp"
+    );
+    assert_eq!(
+        Time::range(
+            Date::new(2024, 7, 26).unwrap(),
+            Date::new(2024, 8, 11).unwrap()
+        )
+        .unwrap()
+        .to_string(),
+        "2024-07-26..2024-08-11"
+    );
+    assert!(matches!("Purple".parse(), Ok(Color::Purple)));
+}
+
+#[test]
+fn rejects_unknown_names() {
+    assert_eq!(
+        "mauve".parse::<Color>().unwrap_err(),
+        ArgumentError::Invalid {
+            argument: "color",
+            value: "mauve".into()
+        }
+    );
+    assert!("2023-02-29".parse::<Date>().is_err());
+    assert!(matches!(
+        "2024-08-11..2024-07-26".parse::<Time>(),
+        Err(ArgumentError::DateRange { .. })
+    ));
+    assert!("larger_than_3mp".parse::<Size>().is_err());
+    for size in ["0x0", "0x600", "800x0"] {
+        assert_eq!(
+            size.parse::<Size>().unwrap_err(),
+            ArgumentError::Invalid {
+                argument: "size",
+                value: size.into()
+            }
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn arguments_round_trip() {
+    use image_search::{Arguments, Bing, Google};
+
+    let args = Arguments::new("cats", 25)
+        .color(Color::Teal)
+        .size(Size::Exact {
+            width: 800,
+            height: 600,
+        })
+        .backend(Bing)
+        .add_backend(Google)
+        .raw_param("tbs", "sur:fmc");
+
+    let json = serde_json::to_value(&args).unwrap();
+    assert_eq!(json["color"], "teal");
+    assert_eq!(json["size"], "800x600");
+    assert_eq!(json["timeout"], 20.0);
+    assert_eq!(json["backends"], serde_json::json!(["bing", "google"]));
+
+    let parsed: Arguments = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.to_url().unwrap(), args.to_url().unwrap());
+    assert_eq!(parsed.get_limit(), 25);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn retry_policy_round_trips() {
+    use image_search::RetryPolicy;
+    use std::time::Duration;
+
+    let retry = RetryPolicy::new()
+        .attempts(5)
+        .backoff(Duration::from_millis(250), Duration::from_secs(4))
+        .retry_on([FailureKind::ServerError, FailureKind::RateLimited]);
+
+    let json = serde_json::to_value(&retry).unwrap();
+    assert_eq!(json["backoff"], 0.25);
+    assert_eq!(
+        json["retry_on"],
+        serde_json::json!(["server_error", "rate_limited"])
+    );
+    assert_eq!(serde_json::from_value::<RetryPolicy>(json).unwrap(), retry);
+
+    let partial: RetryPolicy = serde_json::from_str(r#"{"attempts": 1}"#).unwrap();
+    assert_eq!(partial, RetryPolicy::never());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn missing_fields_use_defaults() {
+    use image_search::{Arguments, Image};
+
+    let args: Arguments =
+        serde_json::from_str(r#"{"query": "cats", "time": "month", "backends": ["bing"]}"#)
+            .unwrap();
+    assert!(matches!(args.get_time(), Time::Month));
+    assert!(matches!(args.get_color(), Color::None));
+    assert_eq!(args.get_backends()[0].name(), "bing");
+
+    assert!(serde_json::from_str::<Arguments>(r#"{"backends": ["altavista"]}"#).is_err());
+    assert!(serde_json::from_str::<Arguments>(r#"{"color": "mauve"}"#).is_err());
+
+    let image: Image = serde_json::from_str(
+        r#"{"url": "https://example.com/a.jpg", "width": 1, "height": 2, "thumbnail": "", "source": ""}"#,
+    )
+    .unwrap();
+    assert_eq!(image.height, 2);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn politeness_is_read_through_its_builders() {
+    use image_search::{Arguments, Politeness};
+    use std::time::Duration;
+
+    for rate in ["0", "-1"] {
+        let json = format!(r#"{{"rate_limit": {}, "burst": 0}}"#, rate);
+        let politeness: Politeness = serde_json::from_str(&json).unwrap();
+        assert_eq!(politeness.get_rate_limit(), None, "{}", rate);
+        assert_eq!(politeness.get_burst(), 1);
+    }
+
+    assert_eq!(
+        Politeness::new().rate_limit(f64::INFINITY).get_rate_limit(),
+        None
+    );
+
+    let politeness: Politeness = serde_json::from_str(r#"{"crawl_delay": 0.5}"#).unwrap();
+    assert_eq!(politeness.get_rate_limit(), Some(4.0));
+    assert_eq!(politeness.get_crawl_delay(), Duration::from_millis(500));
+
+    let args: Arguments =
+        serde_json::from_str(r#"{"query": "cats", "politeness": {"rate_limit": 0}}"#).unwrap();
+    assert_eq!(args.get_politeness().get_rate_limit(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn concurrency_is_at_least_one() {
+    use image_search::Arguments;
+
+    let args: Arguments =
+        serde_json::from_str(r#"{"query": "cats", "concurrency": 0, "host_concurrency": 0}"#)
+            .unwrap();
+    assert_eq!(args.get_concurrency(), 1);
+    assert_eq!(args.get_host_concurrency(), 1);
+}
@@ -0,0 +1,185 @@
+//! Tests that arguments survive being turned into a Google url and read back.
+
+extern crate image_search;
+extern crate proptest;
+
+use image_search::{
+    ArgumentError, Arguments, Color, ColorType, Date, Error, Format, Google, ImageType, License,
+    MinSize, Ratio, SafeSearch, SearchBackend, Size, Time,
+};
+use proptest::prelude::*;
+
+fn arguments() -> impl Strategy<Value = Arguments> {
+    let colors = prop_oneof![
+        Just((Color::None, ColorType::None)),
+        Just((Color::Teal, ColorType::None)),
+        Just((Color::Brown, ColorType::None)),
+        Just((Color::None, ColorType::Grayscale)),
+        Just((Color::None, ColorType::Transparent)),
+    ];
+    let filters = (
+        prop::sample::select(vec![
+            License::None,
+            License::CreativeCommons,
+            License::Other,
+        ]),
+        prop::sample::select(vec![ImageType::None, ImageType::Face, ImageType::Animated]),
+        prop::sample::select(vec![Ratio::None, Ratio::Tall, Ratio::Panoramic]),
+        prop::sample::select(vec![Format::None, Format::Png, Format::Raw]),
+    );
+    let size = prop_oneof![
+        Just(Size::None),
+        Just(Size::Icon),
+        prop::sample::select(vec![MinSize::Qsvga, MinSize::Mp70]).prop_map(Size::LargerThan),
+        (1..5000u32, 1..5000u32).prop_map(|(width, height)| Size::Exact { width, height }),
+    ];
+    let time = prop_oneof![
+        Just(Time::None),
+        Just(Time::Month),
+        (2000..2030u16, 1..=12u8, 1..=28u8, 0..400u16).prop_map(|(year, month, day, days)| {
+            let from = Date::new(year, month, day).unwrap();
+            let to = Date::new(year + 1 + days / 365, month, day).unwrap();
+            Time::range(from, to).unwrap()
+        }),
+    ];
+    let locale = (
+        prop::option::of("[a-z]{2}"),
+        prop::option::of("[a-z]{2}"),
+        prop::option::of("[A-Z]{2}"),
+        prop::sample::select(vec![SafeSearch::None, SafeSearch::Off, SafeSearch::Strict]),
+    );
+
+    ("[a-z &#+\"-]{1,20}", colors, filters, size, time, locale).prop_map(
+        |(
+            query,
+            (color, color_type),
+            (license, image_type, ratio, format),
+            size,
+            time,
+            (language, region, country, safe_search),
+        )| {
+            let mut args = Arguments::new(query, 0)
+                .color(color)
+                .color_type(color_type)
+                .license(license)
+                .image_type(image_type)
+                .ratio(ratio)
+                .format(format)
+                .size(size)
+                .time(time)
+                .safe_search(safe_search);
+
+            if let Some(language) = language {
+                args = args.language(language);
+            }
+            if let Some(region) = region {
+                args = args.region(region);
+            }
+            if let Some(country) = country {
+                args = args.country(country);
+            }
+            args
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn urls_round_trip(args in arguments()) {
+        let url = args.to_url().unwrap();
+        let (parsed, unknown) = Arguments::from_url(&url).unwrap();
+
+        prop_assert!(unknown.is_empty());
+        prop_assert_eq!(parsed.get_query(), args.get_query());
+        prop_assert_eq!(parsed.to_url().unwrap(), url);
+    }
+}
+
+#[test]
+fn reads_browser_urls() {
+    let (args, unknown) = Arguments::from_url(
+        "https://www.google.co.uk/search?q=red+car&tbm=isch&tbs=isz:lt,islt:2mp,il:cl,cdr:1,cd_min:1/2/2020,cd_max:12/31/2020&gl=GB&cr=countryGB&safe=images&start=100",
+    )
+    .unwrap();
+
+    assert!(unknown.is_empty());
+    assert_eq!(args.get_domain(), Some("co.uk"));
+    assert_eq!(args.get_region(), Some("gb"));
+    assert_eq!(args.get_country(), Some("GB"));
+    assert!(matches!(args.get_safe_search(), SafeSearch::Blur));
+    assert!(matches!(args.get_size(), Size::LargerThan(MinSize::Mp2)));
+    assert!(matches!(args.get_license(), License::CreativeCommons));
+    assert!(matches!(
+        args.get_time(),
+        Time::Range { from, to } if from == Date::new(2020, 1, 2).unwrap() && to == Date::new(2020, 12, 31).unwrap()
+    ));
+}
+
+#[test]
+fn keeps_unknown_parameters() {
+    let (args, unknown) = Arguments::from_url(
+        "https://www.google.com/search?q=cats&tbs=itp:photo,sur:fmc&sxsrf=abc&biw=1280",
+    )
+    .unwrap();
+
+    assert_eq!(unknown, ["tbs=sur:fmc", "sxsrf=abc", "biw=1280"]);
+    assert_eq!(
+        args.to_url().unwrap(),
+        "https://www.google.com/search?udm=2&q=cats&tbs=itp%3Aphoto%2Csur%3Afmc&sxsrf=abc&biw=1280"
+    );
+}
+
+#[test]
+fn rejects_invalid_urls() {
+    for url in [
+        "not a url",
+        "https://www.bing.com/images/search?q=cats",
+        "https://www.google.com/maps?q=cats",
+        "https://www.google.evil.com/search?q=cats",
+        "https://google.com.attacker.net/search?q=cats",
+        "https://www.google.com/search?q=cats&tbs=isc:red,ic:gray",
+        "https://www.google.com/search?q=cats&tbs=cdr:1,cd_min:2/1/2021,cd_max:1/1/2021",
+        "https://www.google.com/search?q=cats&tbs=isz:ex,iszw:0,iszh:0",
+    ] {
+        assert!(Arguments::from_url(url).is_err(), "{}", url);
+    }
+
+    let args = Arguments::new("cats", 10).size(Size::Exact {
+        width: 0,
+        height: 0,
+    });
+    assert!(args.to_url().is_err());
+}
+
+#[test]
+fn checks_the_domain() {
+    for domain in ["de", "co.uk", "com.br", ".COM"] {
+        let args = Arguments::new("cats", 10).domain(domain);
+        assert!(args.to_url().is_ok(), "{}", domain);
+    }
+
+    for domain in [
+        "evil.com/",
+        "com.attacker.net",
+        "example",
+        "c",
+        "de:8080",
+        "c0m",
+    ] {
+        let args = Arguments::new("cats", 10).domain(domain);
+        assert!(
+            matches!(
+                args.to_url(),
+                Err(Error::Arguments(ArgumentError::Invalid {
+                    argument: "domain",
+                    ..
+                }))
+            ),
+            "{}",
+            domain
+        );
+        assert!(Google
+            .build_url(&args, 0)
+            .starts_with("https://www.google.com/search?"));
+    }
+}